@@ -30,6 +30,7 @@ fn generate_lints(manifest_dir: &Path) -> Result<()> {
     println!("cargo::rerun-if-changed={}", lint_dir.display());
 
     let mut lint_vars = Vec::new();
+    let mut doc_vars = Vec::new();
     for result in read_dir(&lint_dir)
         .with_context(|| format!("failed to read directory `{}`", lint_dir.display()))?
     {
@@ -56,6 +57,21 @@ fn generate_lints(manifest_dir: &Path) -> Result<()> {
             r####"pub static {lint_var}: (&str, &str) = (r###"{lint_name}"###, r###"{lint_src}"###);"####
         )?;
         let () = lint_vars.push(lint_var);
+
+        let doc_path = lint_path.with_extension("md");
+        let doc_src = read_to_string(&doc_path).with_context(|| {
+            format!(
+                "failed to read documentation `{}` of lint `{}`",
+                doc_path.display(),
+                lint_path.display()
+            )
+        })?;
+        let doc_var = format!("LINT_{lint_name_upper}_DOC");
+        writeln!(
+            &mut lints_rs_file,
+            r####"pub static {doc_var}: &str = r###"{doc_src}"###;"####
+        )?;
+        let () = doc_vars.push(doc_var);
     }
 
     writeln!(
@@ -67,6 +83,18 @@ fn generate_lints(manifest_dir: &Path) -> Result<()> {
         writeln!(&mut lints_rs_file, "    {lint_var},")?;
     }
     writeln!(&mut lints_rs_file, r#"];"#)?;
+
+    // Documentation is stored in the same order as the lints
+    // themselves.
+    writeln!(
+        &mut lints_rs_file,
+        r#"pub static DOCS: [&str; {}] = ["#,
+        doc_vars.len()
+    )?;
+    for doc_var in doc_vars {
+        writeln!(&mut lints_rs_file, "    {doc_var},")?;
+    }
+    writeln!(&mut lints_rs_file, r#"];"#)?;
     Ok(())
 }
 
@@ -1,3 +1,4 @@
+use std::ffi::OsStr;
 use std::fs::File;
 use std::io::BufRead as _;
 use std::io::BufReader;
@@ -5,9 +6,20 @@ use std::path::PathBuf;
 
 use anyhow::Context as _;
 use anyhow::Result;
+use anyhow::bail;
 
 use clap::ArgAction;
 use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use clap::builder::EnumValueParser;
+use clap::builder::PossibleValue;
+use clap::builder::PossibleValuesParser;
+use clap::builder::TypedValueParser;
+use clap::error::ErrorKind;
+
+use bpflint::Severity;
+use bpflint::SuppressionScope;
 
 
 fn parse_files(s: &str) -> Result<Vec<PathBuf>> {
@@ -55,19 +67,219 @@ fn parse_context_line_count(s: &str) -> Result<u8> {
     Ok(line_count)
 }
 
+/// Create a value parser for [`Severity`] values.
+fn severity_parser() -> impl TypedValueParser<Value = Severity> {
+    PossibleValuesParser::new(["note", "warning", "error"])
+        // SANITY: Only valid severities pass the possible value check.
+        .map(|s| s.parse::<Severity>().unwrap())
+}
+
+/// Create a value parser for [`SuppressionScope`] values.
+fn scope_parser() -> impl TypedValueParser<Value = SuppressionScope> {
+    PossibleValuesParser::new(["statement", "function", "file"])
+        // SANITY: Only valid scopes pass the possible value check.
+        .map(|s| s.parse::<SuppressionScope>().unwrap())
+}
+
+/// Information on exit statuses, appended to the help text.
+const EXIT_STATUS_HELP: &str = "\
+Exit status:
+  0  No findings violating the configured failure policy
+  1  Findings violating the policy (see --fail-on and --max-warnings)
+  2  The program failed to run (e.g., a file could not be read)
+  3  At least one source file contains syntax errors (only reported with
+     --syntax-errors)";
+
+/// The format in which to emit output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Human readable output, suitable for display in a terminal.
+    #[default]
+    Terminal,
+    /// Machine readable JSON output.
+    Json,
+    /// Checkstyle XML output (lint results only).
+    Checkstyle,
+    /// XML test report output, with a test case per file (lint results
+    /// only).
+    Junit,
+    /// Code Climate JSON output, as understood by GitLab's Code Quality
+    /// feature (lint results only).
+    Codeclimate,
+    /// GitHub Actions workflow commands, rendered as annotations (lint
+    /// results only).
+    Github,
+    /// A self-contained HTML page with highlighted code snippets and
+    /// filtering (lint results only).
+    Html,
+    /// Markdown output, as suitable for review comments (lint results
+    /// only).
+    Markdown,
+    /// SARIF 2.1.0 JSON output, as understood by code scanning tools
+    /// (lint results only).
+    Sarif,
+}
+
+/// A requested report: the format to produce it in and, optionally,
+/// the path of the file to write it to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Output {
+    /// The format of the report.
+    pub format: Format,
+    /// The path of the file to write the report to.
+    pub path: Option<PathBuf>,
+}
+
+/// A value parser for [`Output`] values, specified as `FMT[=PATH]`.
+#[derive(Clone, Copy, Debug)]
+struct OutputParser;
+
+impl TypedValueParser for OutputParser {
+    type Value = Output;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let value = value
+            .to_str()
+            .ok_or_else(|| clap::Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd))?;
+        let (format, path) = match value.split_once('=') {
+            Some((_format, "")) => {
+                return Err(clap::Error::raw(
+                    ErrorKind::InvalidValue,
+                    format!("missing path in output specification '{value}'\n"),
+                )
+                .with_cmd(cmd))
+            },
+            Some((format, path)) => (format, Some(PathBuf::from(path))),
+            None => (value, None),
+        };
+        let format = EnumValueParser::<Format>::new().parse_ref(cmd, arg, OsStr::new(format))?;
+        Ok(Output { format, path })
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let values = Format::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value);
+        Some(Box::new(values))
+    }
+}
+
+/// When to colorize terminal output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Color {
+    /// Colorize output if it is written to a terminal and the
+    /// `NO_COLOR` environment variable is not set.
+    #[default]
+    Auto,
+    /// Always colorize output.
+    Always,
+    /// Never colorize output.
+    Never,
+}
+
+impl Color {
+    /// Check whether output should be colorized, given whether it is
+    /// written to a terminal and the value of the `NO_COLOR` environment
+    /// variable.
+    pub fn enabled(self, is_terminal: bool, no_color: Option<&OsStr>) -> bool {
+        match self {
+            Self::Auto => is_terminal && no_color.is_none_or(OsStr::is_empty),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+/// A sub-command of the program.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Explain a lint in detail.
+    Explain {
+        /// The name of the lint to explain.
+        #[arg(value_name = "LINT")]
+        lint: String,
+    },
+    /// Run a language server, communicating over stdio.
+    ///
+    /// Diagnostics are published as documents are opened, changed, and
+    /// saved. Additional lints to disable can be provided by the client
+    /// in the `disable` array of its initialization options, and
+    /// syntax errors can be enabled via its `syntaxErrors` flag.
+    Lsp {
+        /// Disable the provided lint (can be supplied multiple times).
+        #[arg(long, value_name = "LINT")]
+        disable: Vec<String>,
+        /// Report code that fails to parse as `syntax-error` diagnostics.
+        #[arg(long)]
+        syntax_errors: bool,
+    },
+}
+
 /// A command line interface for bpflint.
 #[derive(Debug, Parser)]
-#[command(version = env!("VERSION"))]
+#[command(
+    version = env!("VERSION"),
+    after_help = EXIT_STATUS_HELP,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
     /// The BPF C source files to lint.
     ///
     /// Use '@file' syntax to include a (newline separated) list of
     /// files from 'file'.
-    #[arg(required = true, value_name = "[@]SRCS", value_parser = parse_files)]
+    #[arg(
+        required_unless_present = "print_lints",
+        value_name = "[@]SRCS",
+        value_parser = parse_files
+    )]
     pub srcs: Vec<Vec<PathBuf>>,
-    /// Print a list of available lints.
-    #[arg(long, exclusive = true)]
+    /// Print a list of available lints along with their configuration.
+    #[arg(long, conflicts_with_all = ["srcs", "watch"])]
     pub print_lints: bool,
+    /// Explain the provided lint in detail.
+    #[arg(long, value_name = "LINT", exclusive = true)]
+    pub explain: Option<String>,
+    /// Disable the provided lint (can be supplied multiple times).
+    #[arg(long, value_name = "LINT")]
+    pub disable: Vec<String>,
+    /// Report code that fails to parse as `syntax-error` findings.
+    ///
+    /// The parser does not understand all BPF C constructs, such as
+    /// `SEC()` attributes on declarations, so valid code may be
+    /// reported as well. Syntax errors result in an exit status of 3
+    /// only if this option is provided.
+    #[arg(long)]
+    pub syntax_errors: bool,
+    /// The format in which to print lint results and lint lists.
+    ///
+    /// Can be supplied multiple times to produce reports in several
+    /// formats in a single run. Each report is written to PATH, if
+    /// provided, and to the --output file or stdout otherwise.
+    ///
+    /// The terminal and json formats report zero-based line and column
+    /// numbers, while all other formats use 1-based ones.
+    #[arg(
+        long,
+        value_name = "FMT[=PATH]",
+        value_parser = OutputParser,
+        default_value = "terminal"
+    )]
+    pub format: Vec<Output>,
+    /// Write reports without a path of their own to PATH instead of
+    /// stdout.
+    #[arg(short = 'o', long, value_name = "PATH", conflicts_with = "watch")]
+    pub output: Option<PathBuf>,
+    /// When to colorize terminal output.
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = Color::Auto)]
+    pub color: Color,
     /// Increase verbosity (can be supplied multiple times).
     #[arg(short = 'v', long = "verbose", global = true, action = ArgAction::Count)]
     pub verbosity: u8,
@@ -80,9 +292,112 @@ pub struct Args {
     /// Number of lines to show before and after the error line.
     #[arg(short = 'C', long = "context", value_parser = parse_context_line_count, conflicts_with_all = ["before", "after"])]
     pub context: Option<u8>,
+    /// The distance between tab stops when displaying code, in columns.
+    #[arg(long, value_name = "N", default_value_t = 4)]
+    pub tab_width: u8,
+    /// Report nearby matches as part of a single code snippet, instead
+    /// of repeating shared context lines for each of them.
+    #[arg(long)]
+    pub group_matches: bool,
+    /// Do not use or update the on-disk cache of lint results.
+    #[arg(long)]
+    pub no_cache: bool,
+    /// The directory in which to cache lint results.
+    ///
+    /// Results are kept per configuration. Results of other
+    /// configurations are removed once they have not been updated for a
+    /// week.
+    #[arg(
+        long,
+        value_name = "DIR",
+        default_value = ".bpflint-cache",
+        conflicts_with = "no_cache"
+    )]
+    pub cache_dir: PathBuf,
+    /// Watch the source files for changes and re-lint them as they
+    /// happen.
+    ///
+    /// Directories are expanded to the '.bpf.c' files they contain and
+    /// are watched recursively, picking up files created later on.
+    #[arg(long, conflicts_with = "format")]
+    pub watch: bool,
+    /// Print summary statistics after all files have been linted.
+    ///
+    /// Statistics are included in `terminal` and `json` reports. Absent
+    /// a `terminal` report, they are printed to stderr for reports in
+    /// other formats.
+    #[arg(long, conflicts_with = "watch")]
+    pub summary: bool,
+    /// Do not report individual matches in terminal output.
+    ///
+    /// Results are still reflected in the exit status and, if
+    /// requested, the summary.
+    #[arg(short = 'q', long, conflicts_with = "watch")]
+    pub quiet: bool,
+    /// Measure and report the time spent parsing files and running
+    /// individual lints.
+    ///
+    /// Cached results are not used when measuring time. Timings are
+    /// reported just like summary statistics.
+    #[arg(long, conflicts_with = "watch")]
+    pub timings: bool,
+    /// The maximum number of in-progress matches per lint query.
+    ///
+    /// Lints exceeding it may miss matches, which is reported.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=65536))]
+    pub match_limit: Option<u32>,
+    /// The time budget for linting a single file, in milliseconds.
+    ///
+    /// Lints not finishing in time are reported as incomplete.
+    #[arg(long, value_name = "MS")]
+    pub timeout: Option<u64>,
+    /// The minimum severity of findings causing a failure exit status.
+    #[arg(long, value_name = "SEVERITY", default_value = "note", value_parser = severity_parser())]
+    pub fail_on: Severity,
+    /// The maximum number of warnings tolerated before failing.
+    ///
+    /// If set, warnings cause a failure exit status only when more than
+    /// this many are reported, irrespective of --fail-on.
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+    /// Apply fixes suggested by lints to the source files in place.
+    ///
+    /// Remaining matches are reported afterwards.
+    #[arg(long, conflicts_with = "watch")]
+    pub fix: bool,
+    /// Insert comments suppressing all reported matches into the source
+    /// files, applying to the innermost enclosing SCOPE.
+    ///
+    /// Together with --fix, only matches without a suggested fix are
+    /// suppressed.
+    #[arg(long, value_name = "SCOPE", value_parser = scope_parser(), conflicts_with = "watch")]
+    pub suppress: Option<SuppressionScope>,
 }
 
 impl Args {
+    /// Retrieve the requested reports along with their destinations,
+    /// with `None` representing stdout.
+    ///
+    /// Reports without a path of their own are written to the --output
+    /// file, if any. No two reports may share a destination.
+    pub fn outputs(&self) -> Result<Vec<(Format, Option<PathBuf>)>> {
+        let mut outputs = Vec::<(Format, Option<PathBuf>)>::with_capacity(self.format.len());
+        for Output { format, path } in &self.format {
+            let path = path.clone().or_else(|| self.output.clone());
+            if outputs.iter().any(|(_format, other)| *other == path) {
+                if let Some(path) = path {
+                    bail!("multiple reports would be written to `{}`", path.display())
+                } else {
+                    bail!(
+                        "multiple reports would be written to stdout; use FMT=PATH to write reports to files"
+                    )
+                }
+            }
+            let () = outputs.push((*format, path));
+        }
+        Ok(outputs)
+    }
+
     /// Calculate the effective context configuration.
     pub fn additional_options(&self) -> bpflint::Opts {
         let (before, after) = if let Some(context) = self.context {
@@ -94,12 +409,17 @@ impl Args {
         };
 
         // If both are 0 (default), use None
-        if before == 0 && after == 0 {
-            bpflint::Opts::default()
+        let extra_lines = if before == 0 && after == 0 {
+            None
         } else {
-            bpflint::Opts {
-                extra_lines: Some((before, after)),
-            }
+            Some((before, after))
+        };
+
+        bpflint::Opts {
+            extra_lines,
+            tab_width: self.tab_width,
+            group_matches: self.group_matches,
+            ..Default::default()
         }
     }
 }
@@ -182,6 +502,28 @@ mod tests {
         let args = try_parse(["test.c", "-C", "4"]).unwrap();
         let opts = args.additional_options();
         assert_eq!(opts.extra_lines, Some((4, 4)));
+        assert!(!opts.group_matches);
+
+        let args = try_parse(["test.c", "-C", "4", "--group-matches"]).unwrap();
+        let opts = args.additional_options();
+        assert_eq!(opts.extra_lines, Some((4, 4)));
+        assert!(opts.group_matches);
+    }
+
+    /// Test parsing of the tab width argument.
+    #[test]
+    fn tab_width_argument_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.additional_options().tab_width, 4);
+
+        let args = try_parse(["test.c", "--tab-width", "8"]).unwrap();
+        assert_eq!(args.additional_options().tab_width, 8);
+
+        let args = try_parse(["test.c", "--tab-width", "0"]).unwrap();
+        assert_eq!(args.additional_options().tab_width, 0);
+
+        assert!(try_parse(["test.c", "--tab-width", "256"]).is_err());
+        assert!(try_parse(["test.c", "--tab-width", "-1"]).is_err());
     }
 
     /// Test that -C cannot be combined with -A or -B using clap groups.
@@ -200,6 +542,272 @@ mod tests {
         assert!(try_parse(["test.c", "-B", "2", "-A", "4"]).is_ok());
     }
 
+    /// Test parsing of cache related arguments.
+    #[test]
+    fn cache_argument_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert!(!args.no_cache);
+        assert_eq!(args.cache_dir, PathBuf::from(".bpflint-cache"));
+
+        let args = try_parse(["test.c", "--no-cache"]).unwrap();
+        assert!(args.no_cache);
+
+        let args = try_parse(["test.c", "--cache-dir", "/tmp/cache"]).unwrap();
+        assert_eq!(args.cache_dir, PathBuf::from("/tmp/cache"));
+
+        assert!(try_parse(["test.c", "--no-cache", "--cache-dir", "/tmp/cache"]).is_err());
+    }
+
+    /// Test that we can request the explanation of a lint.
+    #[test]
+    fn explain_argument_parsing() {
+        let args = try_parse(["explain", "probe-read"]).unwrap();
+        assert!(args.srcs.is_empty());
+        assert!(
+            matches!(&args.command, Some(Command::Explain { lint }) if lint == "probe-read"),
+            "{args:?}"
+        );
+
+        let args = try_parse(["--explain", "probe-read"]).unwrap();
+        assert!(args.srcs.is_empty());
+        assert_eq!(args.explain.as_deref(), Some("probe-read"));
+
+        assert!(try_parse(["explain"]).is_err());
+        assert!(try_parse(["test.c", "--explain", "probe-read"]).is_err());
+        assert!(try_parse(["--print-lints", "--explain", "probe-read"]).is_err());
+    }
+
+    /// Test that we can request running the language server.
+    #[test]
+    fn lsp_argument_parsing() {
+        let args = try_parse(["lsp"]).unwrap();
+        assert!(args.srcs.is_empty());
+        assert!(
+            matches!(&args.command, Some(Command::Lsp { disable, syntax_errors: false }) if disable.is_empty()),
+            "{args:?}"
+        );
+
+        let args = try_parse([
+            "lsp",
+            "--disable",
+            "probe-read",
+            "--disable",
+            "unstable-attach-point",
+            "--syntax-errors",
+        ])
+        .unwrap();
+        assert!(
+            matches!(&args.command, Some(Command::Lsp { disable, syntax_errors: true }) if disable == &["probe-read", "unstable-attach-point"]),
+            "{args:?}"
+        );
+
+        assert!(try_parse(["lsp", "test.c"]).is_err());
+    }
+
+    /// Test parsing of arguments for editing source files.
+    #[test]
+    fn edit_argument_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert!(!args.fix);
+        assert_eq!(args.suppress, None);
+
+        let args = try_parse(["test.c", "--fix", "--suppress", "function"]).unwrap();
+        assert!(args.fix);
+        assert_eq!(args.suppress, Some(SuppressionScope::Function));
+
+        let args = try_parse(["test.c", "--suppress=statement"]).unwrap();
+        assert_eq!(args.suppress, Some(SuppressionScope::Statement));
+
+        let args = try_parse(["test.c", "--suppress", "file"]).unwrap();
+        assert_eq!(args.suppress, Some(SuppressionScope::File));
+
+        assert!(try_parse(["test.c", "--suppress", "block"]).is_err());
+        assert!(try_parse(["test.c", "--fix", "--watch"]).is_err());
+        assert!(try_parse(["test.c", "--suppress", "statement", "--watch"]).is_err());
+    }
+
+    /// Test parsing of lint listing and configuration related
+    /// arguments.
+    #[test]
+    fn lint_config_argument_parsing() {
+        let args = try_parse(["--print-lints"]).unwrap();
+        assert!(args.print_lints);
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Terminal, None)]);
+        assert!(args.disable.is_empty());
+
+        let args = try_parse([
+            "--print-lints",
+            "--format",
+            "json",
+            "--disable",
+            "foo",
+            "--disable",
+            "bar",
+        ])
+        .unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Json, None)]);
+        assert_eq!(args.disable, vec!["foo".to_string(), "bar".to_string()]);
+
+        let args = try_parse(["test.c", "--disable", "foo"]).unwrap();
+        assert_eq!(args.disable, vec!["foo".to_string()]);
+
+        let args = try_parse(["test.c", "--format", "checkstyle"]).unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Checkstyle, None)]);
+        let args = try_parse(["test.c", "--format", "junit"]).unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Junit, None)]);
+        let args = try_parse(["test.c", "--format", "codeclimate"]).unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Codeclimate, None)]);
+        let args = try_parse(["test.c", "--format", "github"]).unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Github, None)]);
+        let args = try_parse(["test.c", "--format", "html"]).unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Html, None)]);
+        let args = try_parse(["test.c", "--format", "markdown"]).unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Markdown, None)]);
+        let args = try_parse(["test.c", "--format", "sarif"]).unwrap();
+        assert_eq!(args.outputs().unwrap(), vec![(Format::Sarif, None)]);
+
+        assert!(try_parse(["--print-lints", "test.c"]).is_err());
+        assert!(try_parse(["--format", "xml", "test.c"]).is_err());
+        assert!(try_parse(["--format", "json", "--watch", "test.c"]).is_err());
+    }
+
+    /// Test parsing of output related arguments.
+    #[test]
+    fn output_argument_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert!(!args.summary);
+        assert!(!args.quiet);
+
+        let args = try_parse(["test.c", "--summary", "-q"]).unwrap();
+        assert!(args.summary);
+        assert!(args.quiet);
+
+        assert!(try_parse(["test.c", "--summary", "--watch"]).is_err());
+        assert!(try_parse(["test.c", "--quiet", "--watch"]).is_err());
+
+        let args = try_parse(["test.c", "--timings"]).unwrap();
+        assert!(args.timings);
+        assert!(try_parse(["test.c", "--timings", "--watch"]).is_err());
+    }
+
+    /// Test parsing of report format and destination arguments.
+    #[test]
+    fn report_destination_argument_parsing() {
+        let args = try_parse(["test.c", "-o", "report.txt"]).unwrap();
+        assert_eq!(
+            args.outputs().unwrap(),
+            vec![(Format::Terminal, Some(PathBuf::from("report.txt")))]
+        );
+
+        let args = try_parse([
+            "test.c",
+            "--format",
+            "terminal",
+            "--format",
+            "json=report.json",
+            "--format=html=a=b.html",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.format[1],
+            Output {
+                format: Format::Json,
+                path: Some(PathBuf::from("report.json")),
+            }
+        );
+        assert_eq!(
+            args.outputs().unwrap(),
+            vec![
+                (Format::Terminal, None),
+                (Format::Json, Some(PathBuf::from("report.json"))),
+                (Format::Html, Some(PathBuf::from("a=b.html"))),
+            ]
+        );
+
+        let args = try_parse(["test.c", "--format", "json=a.json", "-o", "b.txt"]).unwrap();
+        assert_eq!(
+            args.outputs().unwrap(),
+            vec![(Format::Json, Some(PathBuf::from("a.json")))]
+        );
+
+        let args = try_parse(["test.c", "--format", "json", "--format", "junit"]).unwrap();
+        let err = args.outputs().unwrap_err();
+        assert!(err.to_string().contains("stdout"), "{err}");
+        let args = try_parse([
+            "test.c",
+            "--format",
+            "json",
+            "--format",
+            "junit=out",
+            "-o",
+            "out",
+        ])
+        .unwrap();
+        let err = args.outputs().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "multiple reports would be written to `out`"
+        );
+
+        assert!(try_parse(["test.c", "--format", "json="]).is_err());
+        assert!(try_parse(["test.c", "--format", "xml=report.xml"]).is_err());
+        assert!(try_parse(["test.c", "--watch", "-o", "out"]).is_err());
+    }
+
+    /// Test parsing of query limit related arguments.
+    #[test]
+    fn limit_argument_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.match_limit, None);
+        assert_eq!(args.timeout, None);
+
+        let args = try_parse(["test.c", "--match-limit", "65536", "--timeout", "500"]).unwrap();
+        assert_eq!(args.match_limit, Some(65536));
+        assert_eq!(args.timeout, Some(500));
+
+        assert!(try_parse(["test.c", "--match-limit", "0"]).is_err());
+        assert!(try_parse(["test.c", "--match-limit", "65537"]).is_err());
+        assert!(try_parse(["test.c", "--timeout", "-1"]).is_err());
+    }
+
+    /// Test parsing of the color argument and the resulting decision
+    /// on whether to colorize output.
+    #[test]
+    fn color_argument_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.color, Color::Auto);
+
+        let args = try_parse(["test.c", "--color", "never"]).unwrap();
+        assert_eq!(args.color, Color::Never);
+        assert!(try_parse(["test.c", "--color", "sometimes"]).is_err());
+
+        assert!(Color::Auto.enabled(true, None));
+        assert!(Color::Auto.enabled(true, Some(OsStr::new(""))));
+        assert!(!Color::Auto.enabled(true, Some(OsStr::new("1"))));
+        assert!(!Color::Auto.enabled(false, None));
+        assert!(Color::Always.enabled(false, Some(OsStr::new("1"))));
+        assert!(!Color::Never.enabled(true, None));
+    }
+
+    /// Test parsing of failure policy related arguments.
+    #[test]
+    fn failure_policy_argument_parsing() {
+        let args = try_parse(["test.c"]).unwrap();
+        assert_eq!(args.fail_on, Severity::Note);
+        assert_eq!(args.max_warnings, None);
+        assert!(!args.syntax_errors);
+
+        let args = try_parse(["test.c", "--fail-on", "error", "--max-warnings", "10"]).unwrap();
+        assert_eq!(args.fail_on, Severity::Error);
+        assert_eq!(args.max_warnings, Some(10));
+
+        let args = try_parse(["test.c", "--syntax-errors"]).unwrap();
+        assert!(args.syntax_errors);
+
+        assert!(try_parse(["test.c", "--fail-on", "fatal"]).is_err());
+        assert!(try_parse(["test.c", "--max-warnings", "-1"]).is_err());
+    }
+
     /// Test `parse_context_line_count` function directly.
     #[test]
     fn parse_context_line_count_validation() {
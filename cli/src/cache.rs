@@ -0,0 +1,605 @@
+//! On-disk caching of lint results.
+//!
+//! Results are stored in a per-configuration sub-directory of the
+//! cache directory, with one file per linted source, named after the
+//! hash of its contents. Each entry records the length of the source
+//! as well, which is checked on lookup to guard against hash
+//! collisions. The sub-directory name is derived from the
+//! program version, the set of active lints, and the linting options
+//! affecting results, meaning that any change to them transparently
+//! invalidates all previously cached results.
+//!
+//! Entries are written to a temporary file first and then moved into
+//! place, so that concurrent or interrupted runs never observe partial
+//! entries. Whenever the sub-directory for a configuration is created,
+//! sub-directories of other configurations that have not been modified
+//! for [`MAX_AGE`] are removed, to keep the cache from growing without
+//! bounds.
+
+use std::error::Error as StdError;
+use std::fs::create_dir_all;
+use std::fs::read_dir;
+use std::fs::read_to_string;
+use std::fs::remove_dir_all;
+use std::fs::remove_file;
+use std::fs::rename;
+use std::fs::write;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher as _;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::anyhow;
+
+use tracing::debug;
+use tracing::warn;
+
+use bpflint::LintMatch;
+use bpflint::LintMeta;
+use bpflint::LintOpts;
+use bpflint::LintResults;
+use bpflint::Point;
+use bpflint::Range;
+
+
+/// The header identifying the format of a cache entry.
+const HEADER: &str = "bpflint-cache v7";
+/// The time after which the unmodified cache directory of another
+/// configuration is removed.
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+
+/// Hash `data` into a 64 bit value, using the FNV-1a algorithm.
+///
+/// Contrary to [`DefaultHasher`], the algorithm is fixed and so
+/// entries remain valid across Rust releases.
+fn hash(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Escape `s` so that it does not contain any tab or newline
+/// characters.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            '\t' => escaped.push_str(r"\t"),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The inverse of [`escape`].
+fn unescape(s: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let c = match chars.next() {
+                Some('\\') => '\\',
+                Some('t') => '\t',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                c => return Err(anyhow!("encountered invalid escape sequence `\\{c:?}`")),
+            };
+            let () = unescaped.push(c);
+        } else {
+            let () = unescaped.push(c);
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Serialize `results` for source code of `len` bytes into our
+/// textual cache entry format.
+///
+/// Timing information is not persisted.
+fn serialize(len: usize, results: &LintResults) -> String {
+    let LintResults {
+        matches,
+        suppressed,
+        exceeded_match_limit,
+        ..
+    } = results;
+
+    let exceeded = exceeded_match_limit
+        .iter()
+        .map(|name| escape(name))
+        .collect::<Vec<_>>()
+        .join("\t");
+    let count = matches.len();
+    let mut data = format!("{HEADER}\n{len}\n{suppressed}\n{exceeded}\n{count}\n");
+    for m in matches {
+        let LintMatch {
+            lint_name,
+            message,
+            severity,
+            range,
+            fix,
+        } = m;
+        let Range {
+            bytes,
+            start_point,
+            end_point,
+        } = range;
+
+        let mut line = format!(
+            "{}\t{severity}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            escape(lint_name),
+            bytes.start,
+            bytes.end,
+            start_point.row,
+            start_point.col,
+            end_point.row,
+            end_point.col,
+            escape(message),
+        );
+        // The suggested fix, if any, is stored as an optional trailing
+        // field.
+        if let Some(fix) = fix {
+            let () = line.push('\t');
+            let () = line.push_str(&escape(fix));
+        }
+        let () = line.push('\n');
+        let () = data.push_str(&line);
+    }
+    data
+}
+
+/// Deserialize a cache entry as created by [`serialize`], returning
+/// the length of the source code it belongs to along with the results.
+fn deserialize(data: &str) -> Result<(usize, LintResults)> {
+    fn parse<T>(field: Option<&str>) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: StdError + Send + Sync + 'static,
+    {
+        let field = field.context("cache entry is missing field")?;
+        field
+            .parse()
+            .with_context(|| format!("failed to parse cache entry field `{field}`"))
+    }
+
+    let mut lines = data.lines();
+    if lines.next() != Some(HEADER) {
+        return Err(anyhow!("cache entry has unexpected header"))
+    }
+    let len = parse(lines.next())?;
+    let suppressed = parse(lines.next())?;
+    let exceeded_match_limit = lines
+        .next()
+        .context("cache entry is missing lints exceeding match limit")?
+        .split('\t')
+        .filter(|name| !name.is_empty())
+        .map(unescape)
+        .collect::<Result<_>>()?;
+    // The number of matches guards against truncated entries.
+    let count = parse::<usize>(lines.next())?;
+
+    let matches = lines
+        .map(|line| {
+            let mut fields = line.split('\t');
+            let lint_name = unescape(fields.next().context("cache entry is missing lint name")?)?;
+            let severity = fields
+                .next()
+                .context("cache entry is missing severity")?
+                .parse()?;
+            let start = parse(fields.next())?;
+            let end = parse(fields.next())?;
+            let start_point = Point {
+                row: parse(fields.next())?,
+                col: parse(fields.next())?,
+            };
+            let end_point = Point {
+                row: parse(fields.next())?,
+                col: parse(fields.next())?,
+            };
+            let message = unescape(fields.next().context("cache entry is missing message")?)?;
+            let fix = fields.next().map(unescape).transpose()?;
+            if fields.next().is_some() {
+                return Err(anyhow!("cache entry contains excess fields"))
+            }
+
+            let r#match = LintMatch {
+                lint_name,
+                message,
+                severity,
+                range: Range {
+                    bytes: start..end,
+                    start_point,
+                    end_point,
+                },
+                fix,
+            };
+            Ok(r#match)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if matches.len() != count {
+        return Err(anyhow!(
+            "cache entry contains {} instead of {count} matches",
+            matches.len()
+        ))
+    }
+
+    let results = LintResults {
+        matches,
+        suppressed,
+        exceeded_match_limit,
+        timings: None,
+        _non_exhaustive: (),
+    };
+    Ok((len, results))
+}
+
+
+/// Check whether `name` is the name of a per-configuration cache
+/// directory.
+fn is_cache_dir_name(name: &str) -> bool {
+    name.len() == 16 && name.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+
+/// An on-disk cache of lint results, keyed by source code content.
+#[derive(Debug)]
+pub struct Cache {
+    /// The root directory of the cache.
+    root: PathBuf,
+    /// The directory containing cache entries for the current
+    /// configuration.
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Create a cache storing data below `root`, for results produced
+    /// by the provided set of lints with options `opts`.
+    pub fn new<I>(root: &Path, lints: I, opts: &LintOpts) -> Self
+    where
+        I: IntoIterator<Item = LintMeta>,
+    {
+        // NB: `DefaultHasher` is not guaranteed to be stable across Rust
+        //     releases. A change merely results in a fresh directory,
+        //     though, with stale ones eventually getting pruned.
+        let mut hasher = DefaultHasher::new();
+        let () = env!("VERSION").hash(&mut hasher);
+        for LintMeta { name, source, .. } in lints {
+            let () = name.hash(&mut hasher);
+            let () = source.hash(&mut hasher);
+        }
+        let () = opts.syntax_errors.hash(&mut hasher);
+        let () = opts.match_limit.hash(&mut hasher);
+        let key = hasher.finish();
+
+        Self {
+            root: root.to_path_buf(),
+            dir: root.join(format!("{key:016x}")),
+        }
+    }
+
+    fn entry_path(&self, code: &[u8]) -> PathBuf {
+        self.dir.join(format!("{:016x}", hash(code)))
+    }
+
+    fn try_get(&self, code: &[u8]) -> Result<Option<LintResults>> {
+        let path = self.entry_path(code);
+        let data = match read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read cache entry `{}`", path.display()))
+            },
+        };
+        let (len, results) = deserialize(&data)
+            .with_context(|| format!("failed to parse cache entry `{}`", path.display()))?;
+        if len != code.len() {
+            debug!(
+                "ignoring cache entry `{}` for code of different length",
+                path.display()
+            );
+            return Ok(None)
+        }
+        Ok(Some(results))
+    }
+
+    /// Look up cached lint results for `code`.
+    ///
+    /// Any failure to access the cache is treated as a miss.
+    pub fn get(&self, code: &[u8]) -> Option<LintResults> {
+        match self.try_get(code) {
+            Ok(Some(results)) => {
+                debug!("using cached results from `{}`", self.dir.display());
+                Some(results)
+            },
+            Ok(None) => None,
+            Err(err) => {
+                warn!("{err:?}");
+                None
+            },
+        }
+    }
+
+    /// Remove the cache directories of other configurations that have
+    /// not been modified for `max_age`.
+    fn prune(&self, max_age: Duration) -> Result<()> {
+        let now = SystemTime::now();
+        let entries = read_dir(&self.root)
+            .with_context(|| format!("failed to read cache directory `{}`", self.root.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let is_cache_dir = entry.file_name().to_str().is_some_and(is_cache_dir_name);
+            if !is_cache_dir || path == self.dir || !entry.file_type()?.is_dir() {
+                continue
+            }
+
+            let modified = entry.metadata()?.modified()?;
+            if now.duration_since(modified).unwrap_or_default() >= max_age {
+                debug!("removing stale cache directory `{}`", path.display());
+                let () = remove_dir_all(&path).with_context(|| {
+                    format!("failed to remove cache directory `{}`", path.display())
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn try_put(&self, code: &[u8], results: &LintResults) -> Result<()> {
+        if !self.dir.exists() {
+            let () = create_dir_all(&self.dir).with_context(|| {
+                format!("failed to create cache directory `{}`", self.dir.display())
+            })?;
+            if let Err(err) = self.prune(MAX_AGE) {
+                warn!("failed to prune cache: {err:?}");
+            }
+        }
+
+        // Write to a temporary file first and then move it into place,
+        // so that readers never see partially written entries.
+        let path = self.entry_path(code);
+        let tmp_path = path.with_extension(format!("{}.tmp", process::id()));
+        let result = write(&tmp_path, serialize(code.len(), results))
+            .with_context(|| format!("failed to write cache entry `{}`", tmp_path.display()))
+            .and_then(|()| {
+                rename(&tmp_path, &path).with_context(|| {
+                    format!(
+                        "failed to move cache entry into place at `{}`",
+                        path.display()
+                    )
+                })
+            });
+        if result.is_err() {
+            let _result = remove_file(&tmp_path);
+        }
+        result
+    }
+
+    /// Store lint results `results` for `code` in the cache.
+    ///
+    /// Failure to do so is not considered fatal and merely logged.
+    pub fn put(&self, code: &[u8], results: &LintResults) {
+        if let Err(err) = self.try_put(code, results) {
+            warn!("{err:?}");
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+
+    use bpflint::Severity;
+
+    use tempfile::tempdir;
+
+
+    fn lint_meta(name: &str, source: &str) -> LintMeta {
+        LintMeta {
+            name: name.to_string(),
+            source: source.to_string(),
+            severity: Severity::Warning,
+            category: String::new(),
+            autofix: false,
+            description: String::new(),
+            rationale: String::new(),
+            example_bad: String::new(),
+            example_good: String::new(),
+            url: String::new(),
+            _non_exhaustive: (),
+        }
+    }
+
+    fn results() -> LintResults {
+        let matches = vec![
+            LintMatch {
+                lint_name: "probe-read".to_string(),
+                message: "bpf_probe_read() is deprecated".to_string(),
+                severity: Severity::Warning,
+                range: Range {
+                    bytes: 160..174,
+                    start_point: Point { row: 6, col: 4 },
+                    end_point: Point { row: 6, col: 18 },
+                },
+                fix: None,
+            },
+            LintMatch {
+                lint_name: "weird".to_string(),
+                message: "a\tmessage\nwith \\ special\r characters".to_string(),
+                severity: Severity::Error,
+                range: Range {
+                    bytes: 0..0,
+                    start_point: Point::default(),
+                    end_point: Point::default(),
+                },
+                fix: Some("\t{ fixed }\n".to_string()),
+            },
+        ];
+
+        LintResults {
+            matches,
+            suppressed: 3,
+            exceeded_match_limit: vec!["probe-read".to_string(), "weird\t".to_string()],
+            timings: None,
+            _non_exhaustive: (),
+        }
+    }
+
+
+    /// Check that we can escape and unescape strings losslessly.
+    #[test]
+    fn escape_roundtrip() {
+        for s in ["", "abc", "\t\n\r\\", r"\t", "foo\\\tbar"] {
+            let escaped = escape(s);
+            assert!(!escaped.contains(['\t', '\n', '\r']), "{escaped}");
+            assert_eq!(unescape(&escaped).unwrap(), s);
+        }
+
+        assert!(unescape(r"\x").is_err());
+        assert!(unescape("\\").is_err());
+    }
+
+    /// Check that matches survive a round trip through our cache entry
+    /// format.
+    #[test]
+    fn serialization_roundtrip() {
+        let results = results();
+        let data = serialize(42, &results);
+        let (len, deserialized) = deserialize(&data).unwrap();
+        assert_eq!(len, 42);
+        assert_eq!(format!("{deserialized:?}"), format!("{results:?}"));
+
+        let data = serialize(0, &LintResults::default());
+        let (len, deserialized) = deserialize(&data).unwrap();
+        assert_eq!(len, 0);
+        assert!(deserialized.matches.is_empty());
+        assert_eq!(deserialized.suppressed, 0);
+
+        assert!(deserialize("").is_err());
+        assert!(deserialize(&format!("{HEADER}\n")).is_err());
+        assert!(deserialize(&format!("{HEADER}\n0\n0\n")).is_err());
+        assert!(deserialize(&format!("{HEADER}\n0\n0\n\nfoo\t1\t2\n")).is_err());
+
+        // Truncated entries are rejected.
+        let data = serialize(42, &results);
+        let (truncated, _last) = data.trim_end().rsplit_once('\n').unwrap();
+        assert!(deserialize(truncated).is_err());
+    }
+
+    /// Check that our content hash produces the expected FNV-1a values.
+    #[test]
+    fn content_hashing() {
+        assert_eq!(hash(b""), 0xcbf29ce484222325);
+        assert_eq!(hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash(b"foobar"), 0x85944171f73967e8);
+    }
+
+    /// Check that an entry stored for code of a different length is not
+    /// used, as would happen on a hash collision.
+    #[test]
+    fn colliding_entry() {
+        let dir = tempdir().unwrap();
+        let cache = Cache::new(dir.path(), [], &LintOpts::default());
+        let code = b"int main() {}";
+        let other = b"int main() { return 0; }";
+        let () = cache.put(code, &results());
+        let () = rename(cache.entry_path(code), cache.entry_path(other)).unwrap();
+        assert!(cache.get(other).is_none());
+    }
+
+    /// Check that cached results are keyed by code, lint set, and
+    /// options.
+    #[test]
+    fn cache_lookup() {
+        let dir = tempdir().unwrap();
+        let lints = || [lint_meta("foo", "(identifier)")];
+        let opts = LintOpts::default();
+        let cache = Cache::new(dir.path(), lints(), &opts);
+        let code = b"int main() {}";
+
+        assert!(cache.get(code).is_none());
+        let () = cache.put(code, &results());
+        let cached = cache.get(code).unwrap();
+        assert_eq!(format!("{cached:?}"), format!("{:?}", results()));
+        assert!(cache.get(b"int main() { return 0; }").is_none());
+
+        // A cache with the same configuration sees the same entries.
+        let cache = Cache::new(dir.path(), lints(), &opts);
+        assert!(cache.get(code).is_some());
+
+        // A change to a lint invalidates the entry.
+        let cache = Cache::new(dir.path(), [lint_meta("foo", "(comment)")], &opts);
+        assert!(cache.get(code).is_none());
+
+        // As does a change to the set of lints.
+        let cache = Cache::new(dir.path(), [lint_meta("bar", "(identifier)")], &opts);
+        assert!(cache.get(code).is_none());
+
+        // And so does a change to the reporting of syntax errors.
+        let opts = LintOpts {
+            syntax_errors: true,
+            ..Default::default()
+        };
+        let cache = Cache::new(dir.path(), lints(), &opts);
+        assert!(cache.get(code).is_none());
+    }
+
+    /// Check that cache directories of other configurations are pruned
+    /// once they are stale.
+    #[test]
+    fn cache_pruning() {
+        let dir = tempdir().unwrap();
+        let mkdir = |name: &str, age: Duration| {
+            let path = dir.path().join(name);
+            let () = create_dir_all(&path).unwrap();
+            let () = File::open(&path)
+                .unwrap()
+                .set_modified(SystemTime::now() - age)
+                .unwrap();
+            path
+        };
+        let stale = mkdir("0123456789abcdef", MAX_AGE * 2);
+        let recent = mkdir("fedcba9876543210", MAX_AGE / 2);
+        let unrelated = mkdir("unrelated", MAX_AGE * 2);
+
+        let cache = Cache::new(dir.path(), [], &LintOpts::default());
+        let () = cache.put(b"int main() {}", &results());
+        assert!(cache.get(b"int main() {}").is_some());
+        assert!(!stale.exists());
+        assert!(recent.exists());
+        assert!(unrelated.exists());
+
+        // No temporary files are left behind.
+        let entries = read_dir(&cache.dir).unwrap().count();
+        assert_eq!(entries, 1);
+    }
+
+    /// Make sure that corrupted cache entries are treated as misses.
+    #[test]
+    fn corrupted_entry() {
+        let dir = tempdir().unwrap();
+        let cache = Cache::new(dir.path(), [], &LintOpts::default());
+        let code = b"int main() {}";
+        let () = cache.put(code, &results());
+        let () = write(cache.entry_path(code), "garbage").unwrap();
+        assert!(cache.get(code).is_none());
+    }
+}
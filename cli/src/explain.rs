@@ -0,0 +1,103 @@
+//! Detailed explanation of lints.
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use bpflint::LintMeta;
+
+
+/// Write `text` to `writer`, indenting each line by `indent` spaces.
+fn write_indented(text: &str, indent: usize, writer: &mut dyn Write) -> Result<()> {
+    for line in text.lines() {
+        if line.is_empty() {
+            writeln!(writer)?;
+        } else {
+            writeln!(writer, "{:indent$}{line}", "")?;
+        }
+    }
+    Ok(())
+}
+
+
+/// Write a detailed explanation of `lint` to `writer`.
+pub fn explain(lint: &LintMeta, writer: &mut dyn Write) -> Result<()> {
+    let LintMeta {
+        name,
+        severity,
+        description,
+        rationale,
+        example_bad,
+        example_good,
+        url,
+        ..
+    } = lint;
+
+    writeln!(writer, "{name} ({severity})")?;
+    writeln!(writer)?;
+    let () = write_indented(description, 2, writer)?;
+    writeln!(writer)?;
+    writeln!(writer, "Rationale:")?;
+    let () = write_indented(rationale, 2, writer)?;
+    writeln!(writer)?;
+    writeln!(writer, "Bad example:")?;
+    let () = write_indented(example_bad, 4, writer)?;
+    writeln!(writer)?;
+    writeln!(writer, "Good example:")?;
+    let () = write_indented(example_good, 4, writer)?;
+    writeln!(writer)?;
+    writeln!(writer, "More information: {url}")?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bpflint::Severity;
+
+
+    /// Check that lint explanations are rendered as expected.
+    #[test]
+    fn explanation_rendering() {
+        let lint = LintMeta {
+            name: "foo".to_string(),
+            source: String::new(),
+            severity: Severity::Note,
+            category: "general".to_string(),
+            autofix: false,
+            description: "Flags calls to `foo()`.".to_string(),
+            rationale: "`foo()` is bad.\n\nReally bad.".to_string(),
+            example_bad: "foo();".to_string(),
+            example_good: "if (1) {\n    bar();\n}".to_string(),
+            url: "https://example.com/foo".to_string(),
+            _non_exhaustive: (),
+        };
+
+        let mut output = Vec::new();
+        let () = explain(&lint, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = "\
+foo (note)
+
+  Flags calls to `foo()`.
+
+Rationale:
+  `foo()` is bad.
+
+  Really bad.
+
+Bad example:
+    foo();
+
+Good example:
+    if (1) {
+        bar();
+    }
+
+More information: https://example.com/foo
+";
+        assert_eq!(output, expected);
+    }
+}
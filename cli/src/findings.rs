@@ -0,0 +1,170 @@
+//! Accounting of findings and the policy deciding on failure.
+
+use std::ops::AddAssign;
+
+use serde::Serialize;
+
+use bpflint::LintMatch;
+use bpflint::SYNTAX_ERROR;
+use bpflint::Severity;
+
+
+/// Counts of reported findings, by kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Findings {
+    /// The number of findings with [`Severity::Note`].
+    pub notes: usize,
+    /// The number of findings with [`Severity::Warning`].
+    pub warnings: usize,
+    /// The number of findings with [`Severity::Error`].
+    pub errors: usize,
+    /// The number of syntax errors.
+    pub syntax_errors: usize,
+}
+
+impl Findings {
+    /// Account for the reported match `m`.
+    pub fn add(&mut self, m: &LintMatch) {
+        if m.lint_name == SYNTAX_ERROR {
+            self.syntax_errors += 1;
+            return
+        }
+
+        match m.severity {
+            Severity::Note => self.notes += 1,
+            Severity::Warning => self.warnings += 1,
+            Severity::Error => self.errors += 1,
+        }
+    }
+
+    /// Retrieve the total number of findings.
+    pub fn total(&self) -> usize {
+        self.notes + self.warnings + self.errors + self.syntax_errors
+    }
+
+    /// Check whether the findings constitute a failure, given the
+    /// minimum severity `fail_on` and the maximum number of tolerated
+    /// warnings `max_warnings`.
+    ///
+    /// If `max_warnings` is set, warnings only ever cause a failure if
+    /// there are more than that many, independent of `fail_on`.
+    /// Syntax errors are not considered.
+    pub fn violate(&self, fail_on: Severity, max_warnings: Option<usize>) -> bool {
+        let counts = [
+            (Severity::Note, self.notes),
+            (Severity::Warning, self.warnings),
+            (Severity::Error, self.errors),
+        ];
+
+        counts
+            .into_iter()
+            .any(|(severity, count)| match (severity, max_warnings) {
+                (Severity::Warning, Some(max)) => count > max,
+                _ => severity >= fail_on && count > 0,
+            })
+    }
+}
+
+impl AddAssign for Findings {
+    fn add_assign(&mut self, other: Self) {
+        let Self {
+            notes,
+            warnings,
+            errors,
+            syntax_errors,
+        } = other;
+
+        self.notes += notes;
+        self.warnings += warnings;
+        self.errors += errors;
+        self.syntax_errors += syntax_errors;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bpflint::Point;
+    use bpflint::Range;
+
+
+    /// Check that our failure policy is applied correctly.
+    #[test]
+    fn failure_policy() {
+        let findings = Findings::default();
+        assert!(!findings.violate(Severity::Note, None));
+        assert!(!findings.violate(Severity::Note, Some(0)));
+
+        let findings = Findings {
+            notes: 1,
+            ..Default::default()
+        };
+        assert!(findings.violate(Severity::Note, None));
+        assert!(!findings.violate(Severity::Warning, None));
+        assert!(!findings.violate(Severity::Error, None));
+
+        let findings = Findings {
+            warnings: 2,
+            ..Default::default()
+        };
+        assert!(findings.violate(Severity::Note, None));
+        assert!(findings.violate(Severity::Warning, None));
+        assert!(!findings.violate(Severity::Error, None));
+        assert!(findings.violate(Severity::Note, Some(1)));
+        assert!(!findings.violate(Severity::Note, Some(2)));
+        assert!(findings.violate(Severity::Error, Some(1)));
+
+        let findings = Findings {
+            errors: 1,
+            ..Default::default()
+        };
+        assert!(findings.violate(Severity::Error, None));
+        assert!(findings.violate(Severity::Error, Some(5)));
+
+        let findings = Findings {
+            syntax_errors: 1,
+            ..Default::default()
+        };
+        assert!(!findings.violate(Severity::Note, None));
+    }
+
+    /// Check that we count matches correctly.
+    #[test]
+    fn match_accounting() {
+        let mut m = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo".to_string(),
+            severity: Severity::Note,
+            range: Range {
+                bytes: 0..0,
+                start_point: Point::default(),
+                end_point: Point::default(),
+            },
+            fix: None,
+        };
+
+        let mut findings = Findings::default();
+        let () = findings.add(&m);
+        m.severity = Severity::Error;
+        let () = findings.add(&m);
+        let () = findings.add(&m);
+        m.lint_name = SYNTAX_ERROR.to_string();
+        let () = findings.add(&m);
+
+        let expected = Findings {
+            notes: 1,
+            warnings: 0,
+            errors: 2,
+            syntax_errors: 1,
+        };
+        assert_eq!(findings, expected);
+        assert_eq!(findings.total(), 4);
+
+        let mut sum = findings;
+        sum += findings;
+        assert_eq!(sum.total(), 8);
+        assert_eq!(sum.errors, 4);
+    }
+}
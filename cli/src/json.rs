@@ -0,0 +1,141 @@
+//! Reporting of lint results in JSON format.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use serde::Serialize;
+
+use bpflint::LintMatch;
+use bpflint::Reporter;
+use bpflint::SourceFile;
+
+use crate::summary::Summary;
+use crate::timings::Timings;
+
+
+/// A lint match in a specific file.
+#[derive(Debug, Serialize)]
+struct FileMatch {
+    /// The path to the file in which the match was found.
+    path: PathBuf,
+    #[serde(flatten)]
+    r#match: LintMatch,
+}
+
+
+/// A JSON report of the lint results of a run.
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    /// All matches, in the order in which they were reported.
+    matches: Vec<FileMatch>,
+    /// Summary statistics of the run.
+    summary: Summary,
+    /// Timing information, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<Timings>,
+}
+
+impl Report {
+    /// Set the summary statistics of the run.
+    pub fn set_summary(&mut self, summary: Summary) {
+        self.summary = summary;
+    }
+
+    /// Set the timing information of the run.
+    pub fn set_timings(&mut self, timings: Timings) {
+        self.timings = Some(timings);
+    }
+
+    /// Write the report to `writer`.
+    ///
+    /// As the report includes the summary of the run, it is only written
+    /// once the run is complete.
+    pub fn write(&self, writer: &mut dyn Write) -> Result<()> {
+        let () = serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+impl Reporter for Report {
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let () = self.matches.push(FileMatch {
+            path: file.path().to_path_buf(),
+            r#match: r#match.clone(),
+        });
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use bpflint::Point;
+    use bpflint::Range;
+    use bpflint::Severity;
+
+    use serde_json::Value;
+    use serde_json::json;
+
+
+    /// Check that we produce the expected JSON for a set of matches.
+    #[test]
+    fn report_serialization() {
+        let mut report = Report::default();
+        let file = SourceFile::new(Path::new("foo.bpf.c"), b"");
+        let () = report
+            .report_match(
+                &file,
+                &LintMatch {
+                    lint_name: "probe-read".to_string(),
+                    message: "bpf_probe_read() is deprecated".to_string(),
+                    severity: Severity::Warning,
+                    range: Range {
+                        bytes: 160..174,
+                        start_point: Point { row: 6, col: 4 },
+                        end_point: Point { row: 6, col: 18 },
+                    },
+                    fix: None,
+                },
+            )
+            .unwrap();
+
+        let mut output = Vec::new();
+        let () = report.write(&mut output).unwrap();
+        let output = serde_json::from_slice::<Value>(&output).unwrap();
+        let expected = json!({
+            "matches": [
+                {
+                    "path": "foo.bpf.c",
+                    "lint_name": "probe-read",
+                    "message": "bpf_probe_read() is deprecated",
+                    "severity": "warning",
+                    "range": {
+                        "bytes": { "start": 160, "end": 174 },
+                        "start_point": { "row": 6, "col": 4 },
+                        "end_point": { "row": 6, "col": 18 },
+                    },
+                    "fix": null,
+                },
+            ],
+            "summary": {
+                "files_scanned": 0,
+                "files_with_findings": 0,
+                "notes": 0,
+                "warnings": 0,
+                "errors": 0,
+                "syntax_errors": 0,
+                "suppressed": 0,
+                "matches_per_lint": {},
+                "elapsed_secs": 0.0,
+            },
+        });
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,217 @@
+//! Listing of available lints.
+
+use std::io::Write;
+
+use anyhow::Result;
+use anyhow::bail;
+
+use serde::Serialize;
+
+use bpflint::LintMeta;
+use bpflint::Severity;
+
+use crate::args::Format;
+
+
+/// Information about a lint, as listed to the user.
+#[derive(Debug, Serialize)]
+struct LintInfo<'lint> {
+    name: &'lint str,
+    severity: Severity,
+    category: &'lint str,
+    enabled: bool,
+    autofix: bool,
+    description: &'lint str,
+    url: &'lint str,
+}
+
+impl<'lint> LintInfo<'lint> {
+    fn new(lint: &'lint LintMeta, disabled: &[String]) -> Self {
+        Self {
+            name: &lint.name,
+            severity: lint.severity,
+            category: &lint.category,
+            enabled: !disabled.contains(&lint.name),
+            autofix: lint.autofix,
+            description: &lint.description,
+            url: &lint.url,
+        }
+    }
+}
+
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+/// Print `lints` as a table.
+fn print_table(lints: &[LintInfo<'_>], writer: &mut dyn Write) -> Result<()> {
+    const HEADER: [&str; 5] = ["NAME", "SEVERITY", "CATEGORY", "ENABLED", "AUTOFIX"];
+
+    let rows = lints
+        .iter()
+        .map(|lint| {
+            [
+                lint.name,
+                lint.severity.as_str(),
+                lint.category,
+                yes_no(lint.enabled),
+                yes_no(lint.autofix),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = HEADER.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    for row in [HEADER].iter().chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Print information about `lints` in the given `format`.
+///
+/// Lints contained in `disabled` are reported as such.
+pub fn print_lints(
+    lints: &[LintMeta],
+    disabled: &[String],
+    format: Format,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    let lints = lints
+        .iter()
+        .map(|lint| LintInfo::new(lint, disabled))
+        .collect::<Vec<_>>();
+
+    match format {
+        Format::Terminal => print_table(&lints, writer),
+        Format::Json => {
+            let () = serde_json::to_writer_pretty(&mut *writer, &lints)?;
+            writeln!(writer)?;
+            Ok(())
+        },
+        Format::Checkstyle
+        | Format::Junit
+        | Format::Codeclimate
+        | Format::Github
+        | Format::Html
+        | Format::Markdown
+        | Format::Sarif => {
+            bail!("lints can only be listed in terminal or JSON format")
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::Value;
+    use serde_json::json;
+
+
+    fn lints() -> Vec<LintMeta> {
+        let lint = |name: &str, severity, category: &str, autofix| LintMeta {
+            name: name.to_string(),
+            source: String::new(),
+            severity,
+            category: category.to_string(),
+            autofix,
+            description: format!("Flags `{name}`."),
+            rationale: String::new(),
+            example_bad: String::new(),
+            example_good: String::new(),
+            url: format!("https://example.com/{name}"),
+            _non_exhaustive: (),
+        };
+
+        vec![
+            lint("foo", Severity::Warning, "deprecated", true),
+            lint("foobar-baz", Severity::Error, "general", false),
+        ]
+    }
+
+
+    /// Check that lints are listed in a table as expected.
+    #[test]
+    fn table_printing() {
+        let mut output = Vec::new();
+        let () = print_lints(
+            &lints(),
+            &["foobar-baz".to_string()],
+            Format::Terminal,
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = "\
+NAME        SEVERITY  CATEGORY    ENABLED  AUTOFIX
+foo         warning   deprecated  yes      yes
+foobar-baz  error     general     no       no
+";
+        assert_eq!(output, expected);
+    }
+
+    /// Check that listing lints in a report-only format fails.
+    #[test]
+    fn report_format_printing() {
+        for format in [
+            Format::Checkstyle,
+            Format::Junit,
+            Format::Codeclimate,
+            Format::Github,
+            Format::Html,
+            Format::Markdown,
+            Format::Sarif,
+        ] {
+            let mut output = Vec::new();
+            let err = print_lints(&lints(), &[], format, &mut output).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "lints can only be listed in terminal or JSON format"
+            );
+            assert!(output.is_empty());
+        }
+    }
+
+    /// Check that lints are listed as JSON as expected.
+    #[test]
+    fn json_printing() {
+        let mut output = Vec::new();
+        let () = print_lints(&lints(), &[], Format::Json, &mut output).unwrap();
+        let output = serde_json::from_slice::<Value>(&output).unwrap();
+        let expected = json!([
+            {
+                "name": "foo",
+                "severity": "warning",
+                "category": "deprecated",
+                "enabled": true,
+                "autofix": true,
+                "description": "Flags `foo`.",
+                "url": "https://example.com/foo",
+            },
+            {
+                "name": "foobar-baz",
+                "severity": "error",
+                "category": "general",
+                "enabled": true,
+                "autofix": false,
+                "description": "Flags `foobar-baz`.",
+                "url": "https://example.com/foobar-baz",
+            },
+        ]);
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,937 @@
+//! A language server publishing lint results as diagnostics.
+//!
+//! The server communicates over stdio and keeps all open documents,
+//! along with their syntax trees, in memory. Documents are synchronized
+//! incrementally: on every change only the affected top-level items are
+//! re-linted (see [`bpflint::Document`]). Code actions applying
+//! suggested fixes and suppressing matches are offered for reported
+//! diagnostics.
+//!
+//! Configuration is limited to lints disabled on the command line or
+//! via the `disable` initialization option, syntax error reporting
+//! enabled via `--syntax-errors` or the `syntaxErrors` initialization
+//! option, and `bpflint:` directives in the code itself. There is no
+//! notion of a project configuration file and the server does not
+//! support configuration changes at runtime.
+
+use std::collections::HashMap;
+
+use anyhow::Context as _;
+use anyhow::Result;
+
+use lsp_server::Connection;
+use lsp_server::ErrorCode;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::Response;
+use lsp_types::CodeAction;
+use lsp_types::CodeActionKind;
+use lsp_types::CodeActionOrCommand;
+use lsp_types::CodeActionParams;
+use lsp_types::CodeActionProviderCapability;
+use lsp_types::Diagnostic;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::DidChangeTextDocumentParams;
+use lsp_types::DidCloseTextDocumentParams;
+use lsp_types::DidOpenTextDocumentParams;
+use lsp_types::DidSaveTextDocumentParams;
+use lsp_types::InitializeParams;
+use lsp_types::NumberOrString;
+use lsp_types::Position;
+use lsp_types::PublishDiagnosticsParams;
+use lsp_types::SaveOptions;
+use lsp_types::ServerCapabilities;
+use lsp_types::TextDocumentContentChangeEvent;
+use lsp_types::TextDocumentSyncCapability;
+use lsp_types::TextDocumentSyncKind;
+use lsp_types::TextDocumentSyncOptions;
+use lsp_types::TextDocumentSyncSaveOptions;
+use lsp_types::Uri;
+use lsp_types::WorkspaceEdit;
+use lsp_types::notification::DidChangeTextDocument;
+use lsp_types::notification::DidCloseTextDocument;
+use lsp_types::notification::DidOpenTextDocument;
+use lsp_types::notification::DidSaveTextDocument;
+use lsp_types::notification::Notification as _;
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::request::CodeActionRequest;
+use lsp_types::request::Request as _;
+
+use serde::Deserialize;
+
+use serde_json::Value;
+
+use tracing::warn;
+
+use bpflint::Document;
+use bpflint::LineIndex;
+use bpflint::LintMatch;
+use bpflint::LintOpts;
+use bpflint::Point;
+use bpflint::Range;
+use bpflint::Severity;
+use bpflint::SuppressionScope;
+use bpflint::TextEdit;
+
+
+/// The name reported as the source of diagnostics.
+const SOURCE: &str = "bpflint";
+
+
+/// Options provided by the client as part of the `initialize` request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct InitOptions {
+    /// Lints to disable, in addition to those disabled on the command
+    /// line.
+    disable: Vec<String>,
+    /// Whether to report syntax errors, in addition to when enabled on
+    /// the command line.
+    syntax_errors: bool,
+}
+
+
+/// Convert `point` in `code` into an LSP position.
+///
+/// Rows map to lines directly, but LSP counts characters in UTF-16
+/// code units, whereas columns of [`Point`] are in bytes.
+fn position(code: &[u8], index: &LineIndex, point: Point) -> Position {
+    let start = index.line_start(point.row).unwrap_or(code.len());
+    let end = (start + point.col).min(code.len());
+    let character = String::from_utf8_lossy(&code[start..end])
+        .encode_utf16()
+        .count();
+
+    Position {
+        line: u32::try_from(point.row).unwrap_or(u32::MAX),
+        character: u32::try_from(character).unwrap_or(u32::MAX),
+    }
+}
+
+/// Convert the LSP `position` in `code` into a byte offset and the
+/// corresponding [`Point`].
+///
+/// This is the inverse of [`position`]. Positions past the end of a
+/// line are clamped to the end of the line and positions past the last
+/// line to the end of the code.
+fn offset(code: &[u8], index: &LineIndex, position: Position) -> (usize, Point) {
+    let row = usize::try_from(position.line).unwrap_or(usize::MAX);
+    let Some(start) = index.line_start(row) else {
+        let row = index.line_count() - 1;
+        let start = index.line_start(row).unwrap_or_default();
+        let point = Point {
+            row,
+            col: code.len() - start,
+        };
+        return (code.len(), point)
+    };
+
+    let line = &code[start..];
+    let line = &line[..line.iter().position(|b| *b == b'\n').unwrap_or(line.len())];
+    // Documents are provided by the client as strings and edited at
+    // character boundaries only, so they are always valid UTF-8.
+    let line = String::from_utf8_lossy(line);
+    let character = usize::try_from(position.character).unwrap_or(usize::MAX);
+    let mut units = 0;
+    let col = line
+        .char_indices()
+        .find_map(|(idx, c)| {
+            if units >= character {
+                return Some(idx)
+            }
+            units += c.len_utf16();
+            None
+        })
+        .unwrap_or(line.len());
+
+    (start + col, Point { row, col })
+}
+
+/// Convert the LSP `range` in `code` into a [`Range`].
+fn range(code: &[u8], index: &LineIndex, range: lsp_types::Range) -> Range {
+    let (start, start_point) = offset(code, index, range.start);
+    let (end, end_point) = offset(code, index, range.end);
+    Range {
+        bytes: start..end,
+        start_point,
+        end_point,
+    }
+}
+
+/// Convert `match` in `code` into an LSP diagnostic.
+fn diagnostic(code: &[u8], index: &LineIndex, r#match: &LintMatch) -> Diagnostic {
+    let LintMatch {
+        lint_name,
+        message,
+        severity,
+        range,
+        fix: _,
+    } = r#match;
+
+    let severity = match severity {
+        Severity::Note => DiagnosticSeverity::INFORMATION,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Error => DiagnosticSeverity::ERROR,
+    };
+
+    Diagnostic {
+        range: lsp_types::Range {
+            start: position(code, index, range.start_point),
+            end: position(code, index, range.end_point),
+        },
+        severity: Some(severity),
+        code: Some(NumberOrString::String(lint_name.clone())),
+        source: Some(SOURCE.to_string()),
+        message: message.clone(),
+        ..Default::default()
+    }
+}
+
+/// Convert `edit` in `code` into an LSP text edit.
+fn text_edit(code: &[u8], index: &LineIndex, edit: TextEdit) -> lsp_types::TextEdit {
+    let TextEdit { range, text } = edit;
+    lsp_types::TextEdit {
+        range: lsp_types::Range {
+            start: position(code, index, range.start_point),
+            end: position(code, index, range.end_point),
+        },
+        new_text: text,
+    }
+}
+
+
+/// Convert the lint matches of `document` into LSP diagnostics.
+fn diagnostics(document: &Document) -> Vec<Diagnostic> {
+    let code = document.code();
+    let index = LineIndex::new(code);
+    document
+        .matches()
+        .iter()
+        .map(|r#match| diagnostic(code, &index, r#match))
+        .collect()
+}
+
+/// Create code actions for all matches in `document` whose diagnostics
+/// intersect `range`, with edits applying to the document at `uri`.
+fn code_actions(document: &Document, uri: &Uri, range: lsp_types::Range) -> Vec<CodeAction> {
+    let code = document.code();
+    let index = LineIndex::new(code);
+    let action = |title, diagnostic: &Diagnostic, edit, is_preferred| CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit {
+            changes: Some(HashMap::from([(
+                uri.clone(),
+                vec![text_edit(code, &index, edit)],
+            )])),
+            ..Default::default()
+        }),
+        is_preferred: Some(is_preferred),
+        ..Default::default()
+    };
+
+    let mut actions = Vec::new();
+    for r#match in document.matches() {
+        let diagnostic = diagnostic(code, &index, r#match);
+        if diagnostic.range.start > range.end || diagnostic.range.end < range.start {
+            continue
+        }
+
+        if let Some(edit) = r#match.fix_edit() {
+            let title = "Apply suggested fix".to_string();
+            let () = actions.push(action(title, &diagnostic, edit, true));
+        }
+
+        for scope in [
+            SuppressionScope::Statement,
+            SuppressionScope::Function,
+            SuppressionScope::File,
+        ] {
+            if let Some(edit) = document.suppression_edit(r#match, scope) {
+                let title = format!(
+                    "Disable `{}` for this {}",
+                    r#match.lint_name,
+                    scope.as_str()
+                );
+                let () = actions.push(action(title, &diagnostic, edit, false));
+            }
+        }
+    }
+    actions
+}
+
+
+/// The state of the language server.
+struct Server<'conn> {
+    /// The connection to the client.
+    connection: &'conn Connection,
+    /// The options used for linting documents.
+    opts: LintOpts,
+    /// All open documents.
+    documents: HashMap<Uri, Document>,
+}
+
+impl Server<'_> {
+    /// Lint `text` as the new contents of the document at `uri` and
+    /// publish the resulting diagnostics.
+    fn update(&mut self, uri: Uri, text: String, version: Option<i32>) -> Result<()> {
+        let document = Document::new(text.into_bytes(), self.opts.clone())
+            .with_context(|| format!("failed to lint `{}`", uri.as_str()))?;
+        let _prev = self.documents.insert(uri.clone(), document);
+        self.publish(uri, version)
+    }
+
+    /// Apply `changes` to the document at `uri`, re-lint the affected
+    /// parts, and publish the resulting diagnostics.
+    ///
+    /// If a change can't be applied, the document is dropped and its
+    /// diagnostics are cleared, as it no longer reflects the client's
+    /// state. It is picked up again once the client saves or re-opens
+    /// it.
+    fn change(
+        &mut self,
+        uri: Uri,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: Option<i32>,
+    ) -> Result<()> {
+        if let Err(err) = self.apply_changes(&uri, changes) {
+            let _prev = self.documents.remove(&uri);
+            let () = self.publish(uri, version)?;
+            return Err(err)
+        }
+        self.publish(uri, version)
+    }
+
+    /// Apply `changes` to the document at `uri`.
+    fn apply_changes(
+        &mut self,
+        uri: &Uri,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    ) -> Result<()> {
+        for change in changes {
+            let TextDocumentContentChangeEvent {
+                range: changed,
+                range_length: _,
+                text,
+            } = change;
+
+            let Some(changed) = changed else {
+                // A change without range replaces the entire text.
+                let document = Document::new(text.into_bytes(), self.opts.clone())
+                    .with_context(|| format!("failed to lint `{}`", uri.as_str()))?;
+                let _prev = self.documents.insert(uri.clone(), document);
+                continue
+            };
+
+            let document = self
+                .documents
+                .get_mut(uri)
+                .with_context(|| format!("document `{}` is not open", uri.as_str()))?;
+            // Each change applies to the document as left by the
+            // previous one.
+            let index = LineIndex::new(document.code());
+            let edit = TextEdit {
+                range: range(document.code(), &index, changed),
+                text,
+            };
+            let _matches = document
+                .edit(&edit)
+                .with_context(|| format!("failed to lint `{}`", uri.as_str()))?;
+        }
+        Ok(())
+    }
+
+    /// Publish the diagnostics of the document at `uri`.
+    fn publish(&self, uri: Uri, version: Option<i32>) -> Result<()> {
+        let diagnostics = self
+            .documents
+            .get(&uri)
+            .map(diagnostics)
+            .unwrap_or_default();
+
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        let () = self
+            .connection
+            .sender
+            .send(notification.into())
+            .context("failed to publish diagnostics")?;
+        Ok(())
+    }
+
+    /// Handle a notification sent by the client.
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        let Notification { method, params } = notification;
+        match method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = serde_json::from_value::<DidOpenTextDocumentParams>(params)?;
+                let document = params.text_document;
+                self.update(document.uri, document.text, Some(document.version))
+            },
+            DidChangeTextDocument::METHOD => {
+                let params = serde_json::from_value::<DidChangeTextDocumentParams>(params)?;
+                let document = params.text_document;
+                self.change(document.uri, params.content_changes, Some(document.version))
+            },
+            DidSaveTextDocument::METHOD => {
+                let params = serde_json::from_value::<DidSaveTextDocumentParams>(params)?;
+                let uri = params.text_document.uri;
+                // The document is kept in sync as it changes, so we
+                // only start over if the saved text differs.
+                match (params.text, self.documents.get(&uri)) {
+                    (Some(text), Some(document)) if text.as_bytes() == document.code() => {
+                        self.publish(uri, None)
+                    },
+                    (Some(text), _) => self.update(uri, text, None),
+                    (None, _) => self.publish(uri, None),
+                }
+            },
+            DidCloseTextDocument::METHOD => {
+                let params = serde_json::from_value::<DidCloseTextDocumentParams>(params)?;
+                let uri = params.text_document.uri;
+                let _prev = self.documents.remove(&uri);
+                // Clear all diagnostics of the document.
+                self.publish(uri, None)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle a code action request with parameters `params`.
+    fn code_actions(&self, params: Value) -> Result<Value> {
+        let params = serde_json::from_value::<CodeActionParams>(params)?;
+        let uri = params.text_document.uri;
+        let actions = if let Some(document) = self.documents.get(&uri) {
+            code_actions(document, &uri, params.range)
+                .into_iter()
+                .map(CodeActionOrCommand::CodeAction)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let actions = serde_json::to_value::<Vec<CodeActionOrCommand>>(actions)?;
+        Ok(actions)
+    }
+
+    /// Handle a request sent by the client, other than a shutdown
+    /// request.
+    fn handle_request(&self, request: Request) -> Response {
+        let Request { id, method, params } = request;
+        let result = match method.as_str() {
+            CodeActionRequest::METHOD => self.code_actions(params),
+            _ => {
+                return Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request `{method}`"),
+                )
+            },
+        };
+
+        match result {
+            Ok(result) => Response::new_ok(id, result),
+            Err(err) => Response::new_err(
+                id,
+                ErrorCode::InternalError as i32,
+                format!("failed to handle `{method}` request: {err:?}"),
+            ),
+        }
+    }
+}
+
+
+/// Serve the language server protocol over `connection`, until the
+/// client shuts the server down.
+///
+/// Lints contained in `disabled` are not run. Syntax errors are only
+/// reported if `syntax_errors` is set.
+fn serve(connection: &Connection, mut disabled: Vec<String>, syntax_errors: bool) -> Result<()> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
+                save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                    include_text: Some(true),
+                })),
+                ..Default::default()
+            },
+        )),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+    let params = connection
+        .initialize(serde_json::to_value(capabilities)?)
+        .context("failed to initialize language server")?;
+    let params = serde_json::from_value::<InitializeParams>(params)
+        .context("failed to parse initialization parameters")?;
+    let options = params
+        .initialization_options
+        .map(serde_json::from_value::<InitOptions>)
+        .transpose()
+        .context("failed to parse initialization options")?
+        .unwrap_or_default();
+    let () = disabled.extend(options.disable);
+
+    let mut server = Server {
+        connection,
+        opts: LintOpts {
+            syntax_errors: syntax_errors || options.syntax_errors,
+            disabled_lints: disabled,
+            ..Default::default()
+        },
+        documents: HashMap::new(),
+    };
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break
+                }
+                let response = server.handle_request(request);
+                let () = connection.sender.send(response.into())?;
+            },
+            Message::Notification(notification) => {
+                let method = notification.method.clone();
+                if let Err(err) = server.handle_notification(notification) {
+                    warn!("failed to handle `{method}` notification: {err:?}");
+                }
+            },
+            Message::Response(..) => (),
+        }
+    }
+    Ok(())
+}
+
+/// Run the language server, communicating with the client over stdio.
+///
+/// Lints contained in `disabled` are not run. Syntax errors are only
+/// reported if `syntax_errors` is set.
+pub fn run(disabled: Vec<String>, syntax_errors: bool) -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let () = serve(&connection, disabled, syntax_errors)?;
+    // The writer thread only finishes once the connection is gone.
+    drop(connection);
+    let () = io_threads
+        .join()
+        .context("failed to communicate with language client")?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr as _;
+    use std::thread;
+
+    use lsp_server::RequestId;
+
+    use serde_json::json;
+
+
+    /// Check that byte based points are converted into UTF-16 based
+    /// positions.
+    #[test]
+    fn position_conversion() {
+        let code = "a\näb😀c\n";
+        let index = LineIndex::new(code.as_bytes());
+        let pos = |row, col| position(code.as_bytes(), &index, Point { row, col });
+
+        assert_eq!(pos(0, 1), Position::new(0, 1));
+        assert_eq!(pos(1, 0), Position::new(1, 0));
+        assert_eq!(pos(1, 2), Position::new(1, 1));
+        assert_eq!(pos(1, 3), Position::new(1, 2));
+        assert_eq!(pos(1, 7), Position::new(1, 4));
+        assert_eq!(pos(1, 8), Position::new(1, 5));
+        // Out-of-bounds columns are clamped.
+        assert_eq!(pos(2, 5), Position::new(2, 0));
+    }
+
+    /// Check that UTF-16 based positions are converted into byte
+    /// offsets and points.
+    #[test]
+    fn offset_conversion() {
+        let code = "a\näb😀c\n";
+        let index = LineIndex::new(code.as_bytes());
+        let offset =
+            |line, character| offset(code.as_bytes(), &index, Position::new(line, character));
+
+        assert_eq!(offset(0, 1), (1, Point { row: 0, col: 1 }));
+        assert_eq!(offset(1, 0), (2, Point { row: 1, col: 0 }));
+        assert_eq!(offset(1, 1), (4, Point { row: 1, col: 2 }));
+        assert_eq!(offset(1, 2), (5, Point { row: 1, col: 3 }));
+        assert_eq!(offset(1, 4), (9, Point { row: 1, col: 7 }));
+        assert_eq!(offset(1, 5), (10, Point { row: 1, col: 8 }));
+        // Out-of-bounds characters are clamped to the end of the line.
+        assert_eq!(offset(1, 42), (10, Point { row: 1, col: 8 }));
+        // Out-of-bounds lines are clamped to the end of the code.
+        assert_eq!(offset(2, 0), (11, Point { row: 2, col: 0 }));
+        assert_eq!(offset(5, 3), (11, Point { row: 2, col: 0 }));
+
+        for (row, col) in [(0, 1), (1, 0), (1, 2), (1, 3), (1, 7), (1, 8)] {
+            let point = Point { row, col };
+            let position = position(code.as_bytes(), &index, point);
+            assert_eq!(offset(position.line, position.character).1, point);
+        }
+    }
+
+    /// A scripted language client, talking to a server running in a
+    /// separate thread.
+    struct Client {
+        /// The client side of the connection.
+        connection: Connection,
+        /// The thread running the server.
+        server: Option<thread::JoinHandle<Result<()>>>,
+        /// The ID of the next request.
+        next_id: i32,
+    }
+
+    impl Client {
+        /// Start a server with lints `disabled` and initialize it with
+        /// `options`.
+        fn start(disabled: &[&str], options: Value) -> Self {
+            let (server, client) = Connection::memory();
+            let disabled = disabled.iter().map(ToString::to_string).collect();
+            let server = thread::spawn(move || serve(&server, disabled, false));
+            let mut client = Self {
+                connection: client,
+                server: Some(server),
+                next_id: 0,
+            };
+
+            let response = client.request(
+                "initialize",
+                json!({"capabilities": {}, "initializationOptions": options}),
+            );
+            assert_eq!(
+                response["capabilities"]["textDocumentSync"]["change"],
+                json!(2)
+            );
+            assert_eq!(response["capabilities"]["codeActionProvider"], json!(true));
+            let () = client.notify("initialized", json!({}));
+            client
+        }
+
+        /// Send a request and wait for its response.
+        fn request(&mut self, method: &str, params: Value) -> Value {
+            let id = RequestId::from(self.next_id);
+            self.next_id += 1;
+            let request = Request::new(id.clone(), method.to_string(), params);
+            let () = self.connection.sender.send(request.into()).unwrap();
+
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(response) => {
+                    assert_eq!(response.id, id);
+                    assert!(response.error.is_none(), "{:?}", response.error);
+                    response.result.unwrap_or_default()
+                },
+                message => panic!("unexpected message: {message:?}"),
+            }
+        }
+
+        /// Send a notification.
+        fn notify(&self, method: &str, params: Value) {
+            let notification = Notification::new(method.to_string(), params);
+            let () = self.connection.sender.send(notification.into()).unwrap();
+        }
+
+        /// Wait for published diagnostics.
+        fn diagnostics(&self) -> PublishDiagnosticsParams {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Notification(notification) => {
+                    assert_eq!(notification.method, PublishDiagnostics::METHOD);
+                    serde_json::from_value(notification.params).unwrap()
+                },
+                message => panic!("unexpected message: {message:?}"),
+            }
+        }
+
+        /// Shut down the server.
+        fn shutdown(mut self) {
+            let response = self.request("shutdown", Value::Null);
+            assert_eq!(response, Value::Null);
+            let () = self.notify("exit", Value::Null);
+            let () = self.server.take().unwrap().join().unwrap().unwrap();
+        }
+    }
+
+
+    /// Check that diagnostics are published as documents are opened,
+    /// changed, saved, and closed.
+    #[test]
+    fn diagnostics_publishing() {
+        let uri = "file:///tmp/test.bpf.c";
+        let code = "int main() {\n  /* ä */ bpf_probe_read(a, b, c);\n}\n";
+
+        let client = Client::start(&[], json!({"syntaxErrors": true}));
+        let () = client.notify(
+            "textDocument/didOpen",
+            json!({"textDocument": {"uri": uri, "languageId": "c", "version": 1, "text": code}}),
+        );
+        let params = client.diagnostics();
+        assert_eq!(params.uri, Uri::from_str(uri).unwrap());
+        assert_eq!(params.version, Some(1));
+        assert_eq!(params.diagnostics.len(), 1);
+        let diagnostic = &params.diagnostics[0];
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("probe-read".to_string()))
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.source.as_deref(), Some(SOURCE));
+        // The `ä` takes up two bytes, but only a single UTF-16 code
+        // unit.
+        assert_eq!(
+            diagnostic.range,
+            lsp_types::Range::new(Position::new(1, 10), Position::new(1, 24))
+        );
+
+        let () = client.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": {"uri": uri, "version": 2},
+                "contentChanges": [{"text": "int main() {\n  return 0;\n}\n"}],
+            }),
+        );
+        let params = client.diagnostics();
+        assert_eq!(params.version, Some(2));
+        assert_eq!(params.diagnostics, Vec::new());
+
+        let () = client.notify(
+            "textDocument/didSave",
+            json!({"textDocument": {"uri": uri}, "text": "int main() {\n  return 0\n}\n"}),
+        );
+        let params = client.diagnostics();
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(
+            params.diagnostics[0].code,
+            Some(NumberOrString::String("syntax-error".to_string()))
+        );
+
+        let () = client.notify(
+            "textDocument/didClose",
+            json!({"textDocument": {"uri": uri}}),
+        );
+        let params = client.diagnostics();
+        assert_eq!(params.diagnostics, Vec::new());
+
+        client.shutdown();
+    }
+
+    /// Check that incremental document changes are applied correctly.
+    #[test]
+    fn incremental_sync() {
+        let uri = "file:///tmp/test.bpf.c";
+        let code = "int main() {\n  /* ä */ bpf_probe_read(a, b, c);\n}\n";
+        let change = |client: &Client, version, changes: Value| {
+            client.notify(
+                "textDocument/didChange",
+                json!({"textDocument": {"uri": uri, "version": version}, "contentChanges": changes}),
+            )
+        };
+        let range = |start: (u32, u32), end: (u32, u32)| {
+            lsp_types::Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+        };
+
+        let client = Client::start(&[], Value::Null);
+        let () = client.notify(
+            "textDocument/didOpen",
+            json!({"textDocument": {"uri": uri, "languageId": "c", "version": 1, "text": code}}),
+        );
+        let params = client.diagnostics();
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(params.diagnostics[0].range, range((1, 10), (1, 24)));
+
+        // Insert a function before `main`, with the match moving down
+        // accordingly. Changes apply in sequence.
+        let () = change(
+            &client,
+            2,
+            json!([
+                {"range": range((0, 0), (0, 0)), "text": "void foo() {}\n"},
+                {"range": range((0, 5), (0, 8)), "text": "bar"},
+            ]),
+        );
+        let params = client.diagnostics();
+        assert_eq!(params.version, Some(2));
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(params.diagnostics[0].range, range((2, 10), (2, 24)));
+
+        // Add a second match after the multi-byte character.
+        let () = change(
+            &client,
+            3,
+            json!([{"range": range((2, 9), (2, 9)), "text": "bpf_probe_read(d, e, f); "}]),
+        );
+        let params = client.diagnostics();
+        let ranges = params
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range)
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, [range((2, 9), (2, 23)), range((2, 35), (2, 49))]);
+
+        // Suppress the first one. The directive only covers the
+        // statement directly following it.
+        let () = change(
+            &client,
+            4,
+            json!([{"range": range((2, 0), (2, 0)), "text": "  /* bpflint: disable=probe-read */\n"}]),
+        );
+        let params = client.diagnostics();
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(params.diagnostics[0].range, range((3, 35), (3, 49)));
+
+        // A change without range replaces the entire text.
+        let () = change(&client, 5, json!([{"text": code}]));
+        let params = client.diagnostics();
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(params.diagnostics[0].range, range((1, 10), (1, 24)));
+
+        client.shutdown();
+    }
+
+    /// Check that lints disabled on the command line, in the
+    /// initialization options, or via comments in the code, as well as
+    /// syntax errors not asked for, are not reported.
+    #[test]
+    fn lint_disabling() {
+        let uri = "file:///tmp/test.bpf.c";
+        let code = "int main() {\n  bpf_probe_read(a, b, c);\n}\n";
+        let open = |client: &Client, code: &str| {
+            client.notify(
+                "textDocument/didOpen",
+                json!({"textDocument": {"uri": uri, "languageId": "c", "version": 1, "text": code}}),
+            )
+        };
+
+        let client = Client::start(&["probe-read"], Value::Null);
+        let () = open(&client, code);
+        assert_eq!(client.diagnostics().diagnostics, Vec::new());
+        client.shutdown();
+
+        let client = Client::start(&[], json!({"disable": ["probe-read"]}));
+        let () = open(&client, code);
+        assert_eq!(client.diagnostics().diagnostics, Vec::new());
+        client.shutdown();
+
+        let code =
+            "int main() {\n  /* bpflint: disable=probe-read */\n  bpf_probe_read(a, b, c);\n}\n";
+        let client = Client::start(&[], Value::Null);
+        let () = open(&client, code);
+        assert_eq!(client.diagnostics().diagnostics, Vec::new());
+        client.shutdown();
+
+        // Syntax errors are only reported on request.
+        let client = Client::start(&[], Value::Null);
+        let () = open(&client, "int main() {\n  return 0\n}\n");
+        assert_eq!(client.diagnostics().diagnostics, Vec::new());
+        client.shutdown();
+    }
+
+    /// Check that code actions applying fixes and suppressing matches
+    /// are offered.
+    #[test]
+    fn code_action_provisioning() {
+        let uri = "file:///tmp/test.bpf.c";
+        let code = "int main() {\n  bpf_probe_read(a, b, c);\n}\n";
+
+        let mut client = Client::start(&[], Value::Null);
+        let () = client.notify(
+            "textDocument/didOpen",
+            json!({"textDocument": {"uri": uri, "languageId": "c", "version": 1, "text": code}}),
+        );
+        let _params = client.diagnostics();
+
+        let request = |client: &mut Client, line| {
+            let range = lsp_types::Range::new(Position::new(line, 0), Position::new(line, 80));
+            let response = client.request(
+                "textDocument/codeAction",
+                json!({"textDocument": {"uri": uri}, "range": range, "context": {"diagnostics": []}}),
+            );
+            serde_json::from_value::<Vec<CodeAction>>(response).unwrap()
+        };
+
+        let actions = request(&mut client, 0);
+        assert_eq!(actions, Vec::new());
+
+        let actions = request(&mut client, 1);
+        let titles = actions
+            .iter()
+            .map(|action| action.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            titles,
+            [
+                "Apply suggested fix",
+                "Disable `probe-read` for this statement",
+                "Disable `probe-read` for this function",
+                "Disable `probe-read` for this file",
+            ]
+        );
+
+        let edits = |action: &CodeAction| {
+            assert_eq!(action.kind, Some(CodeActionKind::QUICKFIX));
+            assert_eq!(action.diagnostics.as_ref().unwrap().len(), 1);
+            let (edit_uri, edits) = action
+                .edit
+                .as_ref()
+                .unwrap()
+                .changes
+                .as_ref()
+                .unwrap()
+                .iter()
+                .next()
+                .unwrap();
+            assert_eq!(edit_uri, &Uri::from_str(uri).unwrap());
+            edits.clone()
+        };
+
+        assert_eq!(
+            edits(&actions[0]),
+            [lsp_types::TextEdit {
+                range: lsp_types::Range::new(Position::new(1, 2), Position::new(1, 16)),
+                new_text: "bpf_probe_read_kernel".to_string(),
+            }]
+        );
+        assert_eq!(
+            edits(&actions[1]),
+            [lsp_types::TextEdit {
+                range: lsp_types::Range::new(Position::new(1, 0), Position::new(1, 0)),
+                new_text: "  /* bpflint: disable=probe-read */\n".to_string(),
+            }]
+        );
+
+        client.shutdown();
+    }
+
+    /// Check that unsupported requests are rejected.
+    #[test]
+    fn unsupported_request() {
+        let client = Client::start(&[], Value::Null);
+        let request = Request::new(
+            RequestId::from(42),
+            "textDocument/hover".to_string(),
+            Value::Null,
+        );
+        let () = client.connection.sender.send(request.into()).unwrap();
+        match client.connection.receiver.recv().unwrap() {
+            Message::Response(response) => {
+                assert_eq!(response.id, RequestId::from(42));
+                let error = response.error.unwrap();
+                assert_eq!(error.code, ErrorCode::MethodNotFound as i32);
+            },
+            message => panic!("unexpected message: {message:?}"),
+        }
+        client.shutdown();
+    }
+}
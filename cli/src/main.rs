@@ -1,38 +1,82 @@
 //! A linter for BPF C code.
 
 mod args;
+mod cache;
+mod explain;
+mod findings;
+mod json;
+mod list;
+mod lsp;
+mod output;
+mod summary;
+mod timings;
+mod watch;
 
 use std::env::var_os;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::fs::File;
 use std::fs::read;
+use std::fs::write;
 use std::io;
+use std::io::BufWriter;
+use std::io::IsTerminal as _;
 use std::io::Write as _;
 use std::io::stderr;
 use std::ops::Not as _;
 use std::path::Path;
 use std::process::ExitCode;
 use std::process::Termination;
+use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context as _;
 use anyhow::Error;
 use anyhow::Result;
+use anyhow::anyhow;
 
 use clap::Parser;
 
 use tracing::Level;
+use tracing::debug;
 use tracing::subscriber::set_global_default as set_global_subscriber;
 use tracing_subscriber::FmtSubscriber;
 use tracing_subscriber::filter::EnvFilter;
 use tracing_subscriber::fmt::time::ChronoLocal;
 
+use bpflint::INCOMPLETE_RESULTS;
 use bpflint::LintMatch;
+use bpflint::LintOpts;
+use bpflint::LintResults;
+use bpflint::Opts;
 use bpflint::Point;
 use bpflint::Range;
+use bpflint::Reporter;
+use bpflint::Severity;
+use bpflint::SourceFile;
+use bpflint::SuppressionScope;
+use bpflint::TerminalReporter;
+use bpflint::apply_edits;
 use bpflint::builtin_lints;
-use bpflint::lint;
-use bpflint::report_terminal_opts;
+use bpflint::lint_opts;
+use bpflint::parse;
+use bpflint::suppression_edit;
+
+use crate::args::Format;
+use crate::cache::Cache;
+use crate::output::Sink;
+use crate::summary::Summary;
+use crate::timings::Timings;
+
+
+/// The exit code used when findings violate the configured policy.
+const EXIT_FINDINGS: u8 = 1;
+/// The exit code used when the program failed to run, e.g., because a
+/// file could not be read.
+const EXIT_TOOL_ERROR: u8 = 2;
+/// The exit code used when source code contained syntax errors.
+const EXIT_PARSE_ERROR: u8 = 3;
 
 
 fn has_bpf_c_ext(path: &Path) -> bool {
@@ -49,6 +93,112 @@ fn has_bpf_c_ext(path: &Path) -> bool {
 }
 
 
+/// Lint the file at `path`, returning its contents along with the lint
+/// results.
+///
+/// Results are looked up in and stored to `cache`, if provided.
+fn lint_file(
+    path: &Path,
+    cache: Option<&Cache>,
+    lint_config: &LintOpts,
+) -> Result<(Vec<u8>, LintResults)> {
+    let code = read(path).with_context(|| format!("failed to read `{}`", path.display()))?;
+
+    let match_ext = has_bpf_c_ext(path).not().then(|| LintMatch {
+        lint_name: "bogus-file-extension".to_string(),
+        message: "by convention BPF C code should use the file extension '.bpf.c'".to_string(),
+        severity: Severity::Warning,
+        range: Range {
+            bytes: 0..0,
+            start_point: Point { row: 0, col: 0 },
+            end_point: Point { row: 0, col: 0 },
+        },
+        fix: None,
+    });
+    let mut results = if let Some(results) = cache.and_then(|cache| cache.get(&code)) {
+        results
+    } else {
+        debug!("linting `{}`", path.display());
+        let results = lint_opts(&code, lint_config)
+            .with_context(|| format!("failed to lint `{}`", path.display()))?;
+        // Incomplete results depend on circumstances such as system
+        // load and should not be reused.
+        let complete = !results
+            .matches
+            .iter()
+            .any(|m| m.lint_name == INCOMPLETE_RESULTS);
+        if let Some(cache) = cache.filter(|_| complete) {
+            let () = cache.put(&code, &results);
+        }
+        results
+    };
+
+    if let Some(match_ext) = match_ext {
+        let () = results.matches.insert(0, match_ext);
+    }
+    Ok((code, results))
+}
+
+/// Edit `code` of the file at `path`, applying the fixes suggested for
+/// `matches` if `fix` is set and inserting comments suppressing them in
+/// `suppress` scope, if provided.
+///
+/// Matches with a suggested fix are only suppressed if fixes are not
+/// applied. Returns whether the file was changed.
+fn edit_file(
+    path: &Path,
+    code: &[u8],
+    matches: &[LintMatch],
+    fix: bool,
+    suppress: Option<SuppressionScope>,
+) -> Result<bool> {
+    let tree = suppress
+        .map(|_| parse(code))
+        .transpose()
+        .with_context(|| format!("failed to suppress matches in `{}`", path.display()))?;
+
+    let mut edits = Vec::new();
+    for r#match in matches {
+        if let Some(edit) = r#match.fix_edit().filter(|_| fix) {
+            let () = edits.push(edit);
+        } else if let (Some(scope), Some(tree)) = (suppress, &tree) {
+            let () = edits.extend(suppression_edit(tree, code, r#match, scope));
+        }
+    }
+
+    if edits.is_empty() {
+        return Ok(false)
+    }
+
+    let code = apply_edits(code, &edits);
+    let () = write(path, code).with_context(|| format!("failed to write `{}`", path.display()))?;
+    Ok(true)
+}
+
+/// Create the file at `path` for writing a report to it.
+fn create_file(path: &Path) -> Result<BufWriter<File>> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create `{}`", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Report all `matches` found in `code` of the file at `path` to
+/// `writer`.
+fn report_matches(
+    path: &Path,
+    code: &[u8],
+    matches: &[LintMatch],
+    opts: &Opts,
+    writer: &mut dyn io::Write,
+) -> Result<()> {
+    let file = SourceFile::new(path, code);
+    let mut reporter = TerminalReporter::new(writer, opts.clone());
+    let () = reporter.begin_run()?;
+    let () = reporter.report_file(&file, matches)?;
+    reporter.end_run()
+}
+
+
 enum ExitError {
     Anyhow(Error),
     ExitCode(ExitCode),
@@ -76,13 +226,31 @@ where
 fn main_impl() -> Result<(), ExitError> {
     let args = args::Args::parse();
     let args::Args {
+        command,
         srcs,
         print_lints,
+        explain,
+        disable,
+        syntax_errors,
         verbosity,
+        no_cache,
+        cache_dir,
+        watch,
+        fail_on,
+        max_warnings,
+        fix,
+        suppress,
+        summary: print_summary,
+        quiet,
+        timings: measure_time,
+        match_limit,
+        timeout,
+        color,
         ..
     } = &args;
 
-    let additional_opts = args.additional_options();
+    let outputs = args.outputs()?;
+    let mut additional_opts = args.additional_options();
 
     let level = match verbosity {
         0 => Level::WARN,
@@ -92,6 +260,7 @@ fn main_impl() -> Result<(), ExitError> {
     };
 
     let builder = FmtSubscriber::builder()
+        .with_writer(stderr)
         .with_timer(ChronoLocal::new("%Y-%m-%dT%H:%M:%S%.3f%:z".to_string()));
 
     if let Some(directive) = var_os(EnvFilter::DEFAULT_ENV) {
@@ -108,41 +277,175 @@ fn main_impl() -> Result<(), ExitError> {
             .with_context(|| "failed to set tracing subscriber")?;
     };
 
-    let stdout = io::stdout();
-    let mut stdout = stdout.lock();
-
-    let m_ext_is_c = LintMatch {
-        lint_name: "bogus-file-extension".to_string(),
-        message: "by convention BPF C code should use the file extension '.bpf.c'".to_string(),
-        range: Range {
-            bytes: 0..0,
-            start_point: Point { row: 0, col: 0 },
-            end_point: Point { row: 0, col: 0 },
-        },
+    let (explain, disable, syntax_errors) = match command {
+        Some(args::Command::Explain { lint }) => (Some(lint), disable, syntax_errors),
+        Some(args::Command::Lsp {
+            disable,
+            syntax_errors,
+        }) => (None, disable, syntax_errors),
+        None => (explain.as_ref(), disable, syntax_errors),
     };
 
-    if *print_lints {
-        for lint in builtin_lints() {
-            writeln!(&mut stdout, "{}", lint.name)?;
+    let lints = builtin_lints().collect::<Vec<_>>();
+    if let Some(name) = disable
+        .iter()
+        .find(|name| !lints.iter().any(|lint| &lint.name == *name))
+    {
+        return Err(ExitError::from(anyhow!(
+            "unknown lint `{name}`; use --print-lints to list available lints"
+        )))
+    }
+
+    if let Some(args::Command::Lsp { .. }) = command {
+        // NB: The language server communicates over stdout and has to
+        //     do so before we lock it below.
+        let () = lsp::run(disable.clone(), *syntax_errors)?;
+        return Ok(())
+    }
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    additional_opts.color = color.enabled(stdout.is_terminal(), var_os("NO_COLOR").as_deref());
+
+    if let Some(name) = explain {
+        let lint = lints
+            .iter()
+            .find(|lint| &lint.name == name)
+            .ok_or_else(|| {
+                anyhow!("unknown lint `{name}`; use --print-lints to list available lints")
+            })?;
+        let () = explain::explain(lint, &mut stdout)?;
+        Ok(())
+    } else if *print_lints {
+        let [(format, path)] = outputs.as_slice() else {
+            return Err(ExitError::from(anyhow!(
+                "lints can only be listed in a single format"
+            )))
+        };
+        if let Some(path) = path {
+            let mut file = create_file(path)?;
+            let () = list::print_lints(&lints, disable, *format, &mut file)?;
+            let () = file.flush()?;
+        } else {
+            let () = list::print_lints(&lints, disable, *format, &mut stdout)?;
         }
         Ok(())
     } else {
-        let mut result = Ok(());
-        for src_path in srcs.iter().flatten() {
-            let code = read(src_path)
-                .with_context(|| format!("failed to read `{}`", src_path.display()))?;
-
-            let match_ext = has_bpf_c_ext(src_path).not().then_some(&m_ext_is_c);
-            let matches =
-                lint(&code).with_context(|| format!("failed to lint `{}`", src_path.display()))?;
-            for m in match_ext.into_iter().chain(matches.iter()) {
-                let () = report_terminal_opts(m, &code, src_path, &mut stdout, &additional_opts)?;
-                if result.is_ok() {
-                    result = Err(ExitError::ExitCode(ExitCode::FAILURE));
+        let lint_config = LintOpts {
+            syntax_errors: *syntax_errors,
+            disabled_lints: disable.clone(),
+            timings: *measure_time,
+            match_limit: *match_limit,
+            timeout: timeout.map(Duration::from_millis),
+            ..Default::default()
+        };
+        // Cached results don't carry timing information, so don't use
+        // the cache when asked to measure time.
+        let cache = (*no_cache || *measure_time).not().then(|| {
+            let enabled = lints
+                .iter()
+                .filter(|lint| !disable.contains(&lint.name))
+                .cloned();
+            Cache::new(cache_dir, enabled, &lint_config)
+        });
+        let srcs = srcs.iter().flatten().cloned().collect::<Vec<_>>();
+
+        if *watch {
+            let clear = stdout.is_terminal();
+            let lint = |path: &Path, writer: &mut dyn io::Write| {
+                let (code, results) = lint_file(path, cache.as_ref(), &lint_config)?;
+                let () = report_matches(path, &code, &results.matches, &additional_opts, writer)?;
+                Ok(results.matches.len())
+            };
+            let () = watch::watch(&srcs, lint, clear, &mut stdout)?;
+            Ok(())
+        } else {
+            let start = Instant::now();
+            let mut summary = Summary::default();
+            let mut timings = Timings::default();
+            let no_color = var_os("NO_COLOR");
+            let mut stdout = Some(&mut stdout);
+            let mut sinks = Vec::with_capacity(outputs.len());
+            for (format, path) in &outputs {
+                let (writer, is_terminal) = if let Some(path) = path {
+                    let writer = Box::new(create_file(path)?) as Box<dyn io::Write>;
+                    (writer, false)
+                } else {
+                    // SANITY: `Args::outputs` ensures that at most one
+                    //         report is written to stdout.
+                    let stdout = stdout.take().unwrap();
+                    let is_terminal = stdout.is_terminal();
+                    (Box::new(stdout) as Box<dyn io::Write>, is_terminal)
+                };
+                let opts = Opts {
+                    color: color.enabled(is_terminal, no_color.as_deref()),
+                    ..additional_opts.clone()
+                };
+                let () = sinks.push(Sink::new(*format, writer, opts, *quiet));
+            }
+
+            for sink in &mut sinks {
+                if let Some(reporter) = sink.reporter() {
+                    let () = reporter.begin_run()?;
+                }
+            }
+            for src_path in &srcs {
+                let (mut code, mut results) = lint_file(src_path, cache.as_ref(), &lint_config)?;
+                if edit_file(src_path, &code, &results.matches, *fix, *suppress)? {
+                    // Report what is left after editing.
+                    (code, results) = lint_file(src_path, cache.as_ref(), &lint_config)?;
                 }
+                let () = summary.add(&results);
+                let () = timings.add(src_path, &results);
+
+                let file = SourceFile::new(src_path, &code);
+                for sink in &mut sinks {
+                    if let Some(reporter) = sink.reporter() {
+                        let () = reporter.report_file(&file, &results.matches)?;
+                    }
+                }
+            }
+            for sink in &mut sinks {
+                if let Some(reporter) = sink.reporter() {
+                    let () = reporter.end_run()?;
+                }
+            }
+            summary.elapsed = start.elapsed();
+
+            let timings = measure_time.then_some(&timings);
+            for sink in sinks {
+                let () = sink.finish(&summary, *print_summary, timings)?;
+            }
+
+            // Reports in formats other than terminal and JSON don't
+            // include human readable statistics. Rather than
+            // interleaving them with such reports, we print them to
+            // stderr, unless a terminal report contains them already.
+            let has_terminal = outputs
+                .iter()
+                .any(|(format, _)| *format == Format::Terminal);
+            let has_other = outputs
+                .iter()
+                .any(|(format, _)| !matches!(format, Format::Terminal | Format::Json));
+            if has_other && !has_terminal {
+                let mut stderr = stderr().lock();
+                if *print_summary {
+                    let () = summary.write_terminal(&mut stderr)?;
+                }
+                if let Some(timings) = timings {
+                    let () = timings.write_terminal(&mut stderr)?;
+                }
+            }
+
+            let findings = summary.findings;
+            if findings.syntax_errors > 0 {
+                Err(ExitError::ExitCode(ExitCode::from(EXIT_PARSE_ERROR)))
+            } else if findings.violate(*fail_on, *max_warnings) {
+                Err(ExitError::ExitCode(ExitCode::from(EXIT_FINDINGS)))
+            } else {
+                Ok(())
             }
         }
-        result
     }
 }
 
@@ -160,7 +463,7 @@ impl Termination for ExitResult {
             ExitResult::Err(err) => match err {
                 ExitError::Anyhow(error) => {
                     let _result = writeln!(stderr(), "{error:?}");
-                    ExitCode::FAILURE
+                    ExitCode::from(EXIT_TOOL_ERROR)
                 },
                 ExitError::ExitCode(exit_code) => exit_code,
             },
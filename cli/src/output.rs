@@ -0,0 +1,128 @@
+//! Production of reports in the various supported formats.
+
+use std::io::Write;
+
+use anyhow::Result;
+
+use bpflint::CheckstyleReporter;
+use bpflint::CodeClimateReporter;
+use bpflint::GitHubReporter;
+use bpflint::HtmlReporter;
+use bpflint::JUnitReporter;
+use bpflint::MarkdownReporter;
+use bpflint::Opts;
+use bpflint::Reporter;
+use bpflint::SarifReporter;
+use bpflint::TerminalReporter;
+
+use crate::args::Format;
+use crate::json;
+use crate::summary::Summary;
+use crate::timings::Timings;
+
+
+/// A sink for a report in one of the supported formats, written to
+/// `W`.
+#[derive(Debug)]
+pub enum Sink<W> {
+    /// A terminal report.
+    Terminal(TerminalReporter<W>),
+    /// A terminal report without individual matches.
+    Quiet(W),
+    /// A JSON report.
+    Json(json::Report, W),
+    /// A Checkstyle XML report.
+    Checkstyle(CheckstyleReporter<W>),
+    /// An XML test report.
+    Junit(JUnitReporter<W>),
+    /// A Code Climate JSON report.
+    Codeclimate(CodeClimateReporter<W>),
+    /// GitHub Actions workflow commands.
+    Github(GitHubReporter<W>),
+    /// A self-contained HTML report.
+    Html(HtmlReporter<W>),
+    /// A Markdown report.
+    Markdown(MarkdownReporter<W>),
+    /// A SARIF report.
+    Sarif(SarifReporter<W>),
+}
+
+impl<W> Sink<W>
+where
+    W: Write,
+{
+    /// Create a new [`Sink`] producing a report in `format` and
+    /// writing it to `writer`.
+    pub fn new(format: Format, writer: W, opts: Opts, quiet: bool) -> Self {
+        match format {
+            Format::Terminal if quiet => Self::Quiet(writer),
+            Format::Terminal => Self::Terminal(TerminalReporter::new(writer, opts)),
+            Format::Json => Self::Json(json::Report::default(), writer),
+            Format::Checkstyle => Self::Checkstyle(CheckstyleReporter::new(writer)),
+            Format::Junit => Self::Junit(JUnitReporter::new(writer)),
+            Format::Codeclimate => Self::Codeclimate(CodeClimateReporter::new(writer)),
+            Format::Github => Self::Github(GitHubReporter::new(writer)),
+            Format::Html => Self::Html(HtmlReporter::new(writer, opts)),
+            Format::Markdown => Self::Markdown(MarkdownReporter::new(writer, opts)),
+            Format::Sarif => Self::Sarif(SarifReporter::new(writer)),
+        }
+    }
+
+    /// Retrieve the [`Reporter`] producing the report, if any.
+    pub fn reporter(&mut self) -> Option<&mut dyn Reporter> {
+        match self {
+            Self::Terminal(reporter) => Some(reporter),
+            Self::Quiet(..) => None,
+            Self::Json(report, ..) => Some(report),
+            Self::Checkstyle(reporter) => Some(reporter),
+            Self::Junit(reporter) => Some(reporter),
+            Self::Codeclimate(reporter) => Some(reporter),
+            Self::Github(reporter) => Some(reporter),
+            Self::Html(reporter) => Some(reporter),
+            Self::Markdown(reporter) => Some(reporter),
+            Self::Sarif(reporter) => Some(reporter),
+        }
+    }
+
+    /// Finish the report, after all matches have been reported.
+    ///
+    /// JSON reports always include `summary`, while terminal reports
+    /// only do so if `print_summary` is set. `timings` are included,
+    /// if provided. Reports in other formats don't include either.
+    pub fn finish(
+        self,
+        summary: &Summary,
+        print_summary: bool,
+        timings: Option<&Timings>,
+    ) -> Result<()> {
+        let mut writer = match self {
+            Self::Terminal(reporter) => reporter.into_inner(),
+            Self::Quiet(writer) => writer,
+            Self::Json(mut report, mut writer) => {
+                let () = report.set_summary(summary.clone());
+                if let Some(timings) = timings {
+                    let () = report.set_timings(timings.clone());
+                }
+                let () = report.write(&mut writer)?;
+                let () = writer.flush()?;
+                return Ok(())
+            },
+            Self::Checkstyle(..)
+            | Self::Junit(..)
+            | Self::Codeclimate(..)
+            | Self::Github(..)
+            | Self::Html(..)
+            | Self::Markdown(..)
+            | Self::Sarif(..) => return Ok(()),
+        };
+
+        if print_summary {
+            let () = summary.write_terminal(&mut writer)?;
+        }
+        if let Some(timings) = timings {
+            let () = timings.write_terminal(&mut writer)?;
+        }
+        let () = writer.flush()?;
+        Ok(())
+    }
+}
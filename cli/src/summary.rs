@@ -0,0 +1,205 @@
+//! Summary statistics of a lint run.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use serde::Serialize;
+use serde::Serializer;
+
+use bpflint::LintResults;
+
+use crate::findings::Findings;
+
+
+/// Serialize `duration` as fractional seconds.
+pub fn serialize_secs<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+
+/// Summary statistics of a lint run.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Summary {
+    /// The number of files scanned.
+    #[serde(rename = "files_scanned")]
+    pub files: usize,
+    /// The number of files with at least one finding.
+    pub files_with_findings: usize,
+    /// The reported findings, by kind.
+    #[serde(flatten)]
+    pub findings: Findings,
+    /// The number of matches suppressed by comments in the code.
+    pub suppressed: usize,
+    /// The number of reported matches, by lint.
+    #[serde(rename = "matches_per_lint")]
+    pub lints: BTreeMap<String, usize>,
+    /// The time it took to lint all files.
+    #[serde(rename = "elapsed_secs", serialize_with = "serialize_secs")]
+    pub elapsed: Duration,
+}
+
+impl Summary {
+    /// Account for the `results` of linting a single file.
+    pub fn add(&mut self, results: &LintResults) {
+        self.files += 1;
+        if !results.matches.is_empty() {
+            self.files_with_findings += 1;
+        }
+
+        for m in &results.matches {
+            let () = self.findings.add(m);
+            *self.lints.entry(m.lint_name.clone()).or_default() += 1;
+        }
+        self.suppressed += results.suppressed;
+    }
+
+    /// Write the summary in human readable form to `writer`.
+    pub fn write_terminal(&self, writer: &mut dyn Write) -> Result<()> {
+        let Self {
+            files,
+            files_with_findings,
+            findings,
+            suppressed,
+            lints,
+            elapsed,
+        } = self;
+
+        let lines = [
+            ("Files scanned:", files.to_string()),
+            ("Files with findings:", files_with_findings.to_string()),
+            ("Matches:", findings.total().to_string()),
+            ("Errors:", findings.errors.to_string()),
+            ("Warnings:", findings.warnings.to_string()),
+            ("Notes:", findings.notes.to_string()),
+            ("Syntax errors:", findings.syntax_errors.to_string()),
+            ("Suppressed:", suppressed.to_string()),
+            ("Elapsed time:", format!("{:.3}s", elapsed.as_secs_f64())),
+        ];
+
+        writeln!(writer, "Summary:")?;
+        for (label, value) in lines {
+            writeln!(writer, "  {label:<22}{value}")?;
+        }
+
+        if !lints.is_empty() {
+            // Align counts with the values above, unless lint names are
+            // too long for that.
+            let width = lints
+                .keys()
+                .map(|lint| lint.len() + 3)
+                .max()
+                .unwrap_or_default()
+                .max(20);
+            writeln!(writer, "  Matches by lint:")?;
+            for (lint, count) in lints {
+                let label = format!("{lint}:");
+                writeln!(writer, "    {label:<width$}{count}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bpflint::LintMatch;
+    use bpflint::Point;
+    use bpflint::Range;
+    use bpflint::SYNTAX_ERROR;
+    use bpflint::Severity;
+
+    use serde_json::json;
+
+
+    fn summary() -> Summary {
+        let m = |lint_name: &str, severity| LintMatch {
+            lint_name: lint_name.to_string(),
+            message: String::new(),
+            severity,
+            range: Range {
+                bytes: 0..0,
+                start_point: Point::default(),
+                end_point: Point::default(),
+            },
+            fix: None,
+        };
+
+        let mut summary = Summary::default();
+        let () = summary.add(&LintResults {
+            matches: vec![
+                m("probe-read", Severity::Warning),
+                m(SYNTAX_ERROR, Severity::Error),
+                m("probe-read", Severity::Warning),
+            ],
+            suppressed: 1,
+            ..Default::default()
+        });
+        let () = summary.add(&LintResults {
+            suppressed: 2,
+            ..Default::default()
+        });
+        let () = summary.add(&LintResults {
+            matches: vec![m("unstable-attach-point", Severity::Note)],
+            ..Default::default()
+        });
+        summary.elapsed = Duration::from_millis(1500);
+        summary
+    }
+
+
+    /// Check that the summary is rendered as expected.
+    #[test]
+    fn terminal_rendering() {
+        let mut output = Vec::new();
+        let () = summary().write_terminal(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = "\
+Summary:
+  Files scanned:        3
+  Files with findings:  2
+  Matches:              4
+  Errors:               0
+  Warnings:             2
+  Notes:                1
+  Syntax errors:        1
+  Suppressed:           3
+  Elapsed time:         1.500s
+  Matches by lint:
+    probe-read:             2
+    syntax-error:           1
+    unstable-attach-point:  1
+";
+        assert_eq!(output, expected);
+    }
+
+    /// Check that the summary is serialized as expected.
+    #[test]
+    fn json_serialization() {
+        let summary = serde_json::to_value(summary()).unwrap();
+        let expected = json!({
+            "files_scanned": 3,
+            "files_with_findings": 2,
+            "notes": 1,
+            "warnings": 2,
+            "errors": 0,
+            "syntax_errors": 1,
+            "suppressed": 3,
+            "matches_per_lint": {
+                "probe-read": 2,
+                "syntax-error": 1,
+                "unstable-attach-point": 1,
+            },
+            "elapsed_secs": 1.5,
+        });
+        assert_eq!(summary, expected);
+    }
+}
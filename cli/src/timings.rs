@@ -0,0 +1,221 @@
+//! Accounting and reporting of the time spent linting.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use serde::Serialize;
+use serde::Serializer;
+
+use bpflint::LintResults;
+
+use crate::summary::serialize_secs;
+
+
+/// The number of slowest files to report.
+const SLOWEST_FILES: usize = 5;
+
+
+fn serialize_secs_map<S>(map: &BTreeMap<String, Duration>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_map(map.iter().map(|(name, time)| (name, time.as_secs_f64())))
+}
+
+fn format_secs(duration: &Duration) -> String {
+    format!("{:.3}s", duration.as_secs_f64())
+}
+
+
+/// The time spent on linting a single file.
+#[derive(Clone, Debug, Serialize)]
+struct FileTime {
+    /// The path to the file.
+    path: PathBuf,
+    /// The time spent parsing and linting the file.
+    #[serde(rename = "secs", serialize_with = "serialize_secs")]
+    time: Duration,
+}
+
+
+/// Timing information accumulated over all linted files.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Timings {
+    /// The total time spent parsing files.
+    #[serde(rename = "parse_secs", serialize_with = "serialize_secs")]
+    parse: Duration,
+    /// The total time spent running each lint.
+    #[serde(rename = "lint_secs", serialize_with = "serialize_secs_map")]
+    lints: BTreeMap<String, Duration>,
+    /// The slowest files, in order of descending time.
+    slowest_files: Vec<FileTime>,
+    /// The number of files for which a lint's query exceeded the
+    /// match limit, by lint.
+    exceeded_match_limit: BTreeMap<String, usize>,
+}
+
+impl Timings {
+    /// Account for the `results` of linting the file at `path`.
+    ///
+    /// Results without timing information (e.g., because they were
+    /// retrieved from the cache) only contribute match limit events.
+    pub fn add(&mut self, path: &Path, results: &LintResults) {
+        for lint in &results.exceeded_match_limit {
+            *self.exceeded_match_limit.entry(lint.clone()).or_default() += 1;
+        }
+
+        let Some(timings) = &results.timings else {
+            return
+        };
+
+        let mut time = timings.parse;
+        self.parse += timings.parse;
+        for (lint, lint_time) in &timings.lints {
+            *self.lints.entry(lint.clone()).or_default() += *lint_time;
+            time += *lint_time;
+        }
+
+        let idx = self.slowest_files.partition_point(|file| file.time >= time);
+        if idx < SLOWEST_FILES {
+            let file = FileTime {
+                path: path.to_path_buf(),
+                time,
+            };
+            let () = self.slowest_files.insert(idx, file);
+            let () = self.slowest_files.truncate(SLOWEST_FILES);
+        }
+    }
+
+    /// Write the timing report in human readable form to `writer`.
+    pub fn write_terminal(&self, writer: &mut dyn Write) -> Result<()> {
+        let Self {
+            parse,
+            lints,
+            slowest_files,
+            exceeded_match_limit,
+        } = self;
+
+        let total = lints.values().sum::<Duration>();
+        writeln!(writer, "Timings:")?;
+        writeln!(writer, "  {:<22}{}", "Parsing:", format_secs(parse))?;
+        writeln!(writer, "  {:<22}{}", "Linting:", format_secs(&total))?;
+
+        let mut lints = lints.iter().collect::<Vec<_>>();
+        let () = lints.sort_by_key(|(_, time)| Reverse(*time));
+        let rows = lints
+            .into_iter()
+            .map(|(lint, time)| (lint.clone(), format_secs(time)))
+            .collect::<Vec<_>>();
+        let () = write_rows("Lints:", &rows, writer)?;
+
+        let rows = slowest_files
+            .iter()
+            .map(|file| (file.path.display().to_string(), format_secs(&file.time)))
+            .collect::<Vec<_>>();
+        let () = write_rows("Slowest files:", &rows, writer)?;
+
+        let rows = exceeded_match_limit
+            .iter()
+            .map(|(lint, count)| (lint.clone(), format!("{count} file(s)")))
+            .collect::<Vec<_>>();
+        let () = write_rows("Match limit exceeded:", &rows, writer)?;
+        Ok(())
+    }
+}
+
+
+/// Write a titled list of `(label, value)` rows, if non-empty.
+fn write_rows(title: &str, rows: &[(String, String)], writer: &mut dyn Write) -> Result<()> {
+    if rows.is_empty() {
+        return Ok(())
+    }
+
+    // Align values with those of the top-level entries, unless labels
+    // are too long for that.
+    let width = rows
+        .iter()
+        .map(|(label, _)| label.len() + 3)
+        .max()
+        .unwrap_or_default()
+        .max(20);
+    writeln!(writer, "  {title}")?;
+    for (label, value) in rows {
+        let label = format!("{label}:");
+        writeln!(writer, "    {label:<width$}{value}")?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bpflint::LintTimings;
+
+    use serde_json::json;
+
+
+    fn results(parse: u64, lints: &[(&str, u64)]) -> LintResults {
+        let lints = lints
+            .iter()
+            .map(|(name, millis)| (name.to_string(), Duration::from_millis(*millis)))
+            .collect();
+        LintResults {
+            timings: Some(LintTimings {
+                parse: Duration::from_millis(parse),
+                lints,
+            }),
+            ..Default::default()
+        }
+    }
+
+
+    /// Check that timings are accumulated and reported as expected.
+    #[test]
+    fn timing_accounting() {
+        let mut timings = Timings::default();
+        for idx in 0..7 {
+            let () = timings.add(
+                Path::new(&format!("{idx}.bpf.c")),
+                &results(idx, &[("foo", 10), ("bar", 100 * idx)]),
+            );
+        }
+        let mut cached = LintResults::default();
+        let () = cached.exceeded_match_limit.push("foo".to_string());
+        let () = timings.add(Path::new("cached.bpf.c"), &cached);
+
+        let mut output = Vec::new();
+        let () = timings.write_terminal(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = "\
+Timings:
+  Parsing:              0.021s
+  Linting:              2.170s
+  Lints:
+    bar:                2.100s
+    foo:                0.070s
+  Slowest files:
+    6.bpf.c:            0.616s
+    5.bpf.c:            0.515s
+    4.bpf.c:            0.414s
+    3.bpf.c:            0.313s
+    2.bpf.c:            0.212s
+  Match limit exceeded:
+    foo:                1 file(s)
+";
+        assert_eq!(output, expected);
+
+        let value = serde_json::to_value(&timings).unwrap();
+        assert_eq!(value["parse_secs"], json!(0.021));
+        assert_eq!(value["lint_secs"]["bar"], json!(2.1));
+        assert_eq!(value["slowest_files"][0]["path"], json!("6.bpf.c"));
+        assert_eq!(value["exceeded_match_limit"], json!({"foo": 1}));
+    }
+}
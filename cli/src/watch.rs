@@ -0,0 +1,509 @@
+//! Watch mode: re-lint files as they change.
+
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::env::current_dir;
+use std::fs::read_dir;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::bail;
+
+use notify::Event;
+use notify::RecursiveMode;
+use notify::Watcher as _;
+use notify::recommended_watcher;
+
+use tracing::debug;
+use tracing::warn;
+
+use crate::has_bpf_c_ext;
+
+
+/// The time we wait for additional events after receiving one, so that
+/// bursts of changes (as caused by editors saving files) result in only
+/// a single re-lint.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The ANSI escape sequence for clearing the screen and moving the
+/// cursor to the top left corner.
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+
+/// The linting state of a single watched file.
+#[derive(Debug)]
+struct FileState {
+    /// The terminal report for the file.
+    report: Vec<u8>,
+    /// The number of findings reported for the file.
+    findings: usize,
+}
+
+impl FileState {
+    fn new<F>(lint: &mut F, path: &Path) -> Self
+    where
+        F: FnMut(&Path, &mut dyn Write) -> Result<usize>,
+    {
+        let mut report = Vec::new();
+        match lint(path, &mut report) {
+            Ok(findings) => Self { report, findings },
+            Err(err) => {
+                // We don't want to stop watching just because a file
+                // is temporarily not available or similar. So we just
+                // render the error in place of the report.
+                let mut report = Vec::new();
+                let _result = writeln!(&mut report, "error: {err:#}");
+                Self {
+                    report,
+                    findings: 0,
+                }
+            },
+        }
+    }
+}
+
+
+/// A file being watched.
+#[derive(Debug)]
+struct File {
+    /// The path of the file, as provided by the user or as discovered
+    /// in a watched directory.
+    path: PathBuf,
+    /// The absolute path of the file, as reported by the watcher.
+    absolute: PathBuf,
+    /// Whether the file was discovered in a directory (as opposed to
+    /// having been provided explicitly).
+    discovered: bool,
+    /// The linting state of the file.
+    state: FileState,
+}
+
+
+/// Collect all `.bpf.c` files in `dir` and its sub-directories, in
+/// lexical order.
+fn bpf_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = read_dir(dir)
+        .with_context(|| format!("failed to read directory `{}`", dir.display()))?
+        .map(|entry| {
+            let entry = entry?;
+            Ok((entry.path(), entry.file_type()?))
+        })
+        .collect::<io::Result<Vec<_>>>()
+        .with_context(|| format!("failed to read directory `{}`", dir.display()))?;
+    let () = entries.sort_by(|(path1, _), (path2, _)| path1.cmp(path2));
+
+    for (path, file_type) in entries {
+        if file_type.is_dir() {
+            let () = bpf_files(&path, files)?;
+        } else if has_bpf_c_ext(&path) {
+            let () = files.push(path);
+        }
+    }
+    Ok(())
+}
+
+
+/// The set of files being watched, along with their linting state.
+#[derive(Debug)]
+struct Files {
+    /// The watched files, in the order in which they are reported.
+    files: Vec<File>,
+    /// The directories in which files are discovered, as provided by
+    /// the user and in absolute form.
+    dirs: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Files {
+    /// Create the set of files to watch from `paths`, expanding
+    /// directories to the `.bpf.c` files they contain, and lint all of
+    /// them.
+    fn new<F>(paths: &[PathBuf], cwd: &Path, lint: &mut F) -> Result<Self>
+    where
+        F: FnMut(&Path, &mut dyn Write) -> Result<usize>,
+    {
+        let mut slf = Self {
+            files: Vec::new(),
+            dirs: Vec::new(),
+        };
+
+        for path in paths {
+            let absolute = cwd.join(path);
+            if absolute.is_dir() {
+                let () = slf.dirs.push((path.clone(), absolute.clone()));
+                let () = slf.discover(&absolute, cwd, lint)?;
+            } else {
+                if path.file_name().is_none() {
+                    bail!("`{}` does not refer to a file or directory", path.display())
+                }
+                let () = slf.add(path.clone(), cwd, false, lint);
+            }
+        }
+        Ok(slf)
+    }
+
+    /// Add all `.bpf.c` files in the directory at the absolute path
+    /// `absolute`, which is expected to reside in one of the watched
+    /// directories.
+    fn discover<F>(&mut self, absolute: &Path, cwd: &Path, lint: &mut F) -> Result<()>
+    where
+        F: FnMut(&Path, &mut dyn Write) -> Result<usize>,
+    {
+        let mut files = Vec::new();
+        let () = bpf_files(absolute, &mut files)?;
+        for file in files {
+            if let Some(path) = self.relative(&file) {
+                let () = self.add(path, cwd, true, lint);
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert the absolute path `absolute` into one relative to the
+    /// watched directory containing it, as provided by the user.
+    fn relative(&self, absolute: &Path) -> Option<PathBuf> {
+        self.dirs.iter().find_map(|(dir, abs_dir)| {
+            absolute
+                .strip_prefix(abs_dir)
+                .ok()
+                .map(|suffix| dir.join(suffix))
+        })
+    }
+
+    /// Add the file at `path` to the set and lint it.
+    fn add<F>(&mut self, path: PathBuf, cwd: &Path, discovered: bool, lint: &mut F)
+    where
+        F: FnMut(&Path, &mut dyn Write) -> Result<usize>,
+    {
+        let absolute = cwd.join(&path);
+        // Files discovered in multiple directories are only reported
+        // once.
+        if discovered && self.files.iter().any(|file| file.absolute == absolute) {
+            return
+        }
+
+        let state = FileState::new(lint, &path);
+        let () = self.files.push(File {
+            path,
+            absolute,
+            discovered,
+            state,
+        });
+    }
+
+    /// Update the set of files and their linting state in response to
+    /// changes to the (absolute) `paths`.
+    ///
+    /// Known files are re-linted, `.bpf.c` files created in watched
+    /// directories get added, and discovered files that have been
+    /// removed are dropped. Returns `true` if anything changed.
+    fn update<F>(&mut self, paths: &BTreeSet<PathBuf>, cwd: &Path, lint: &mut F) -> bool
+    where
+        F: FnMut(&Path, &mut dyn Write) -> Result<usize>,
+    {
+        let count = self.files.len();
+        let () = self.files.retain(|file| {
+            !file.discovered || !paths.contains(&file.absolute) || file.absolute.exists()
+        });
+        let mut updated = self.files.len() != count;
+
+        for file in &mut self.files {
+            if paths.contains(&file.absolute) {
+                file.state = FileState::new(lint, &file.path);
+                updated = true;
+            }
+        }
+
+        let count = self.files.len();
+        for absolute in paths {
+            if self.files.iter().any(|file| &file.absolute == absolute) {
+                continue
+            }
+
+            let Some(path) = self.relative(absolute) else {
+                continue
+            };
+
+            if absolute.is_dir() {
+                // A directory may have been populated before we
+                // started watching it, so scan it.
+                if let Err(err) = self.discover(absolute, cwd, lint) {
+                    warn!("{err:#}");
+                }
+            } else if has_bpf_c_ext(&path) && absolute.is_file() {
+                let () = self.add(path, cwd, true, lint);
+            }
+        }
+        updated || self.files.len() != count
+    }
+
+    /// Retrieve the linting states of all files.
+    fn states(&self) -> impl Iterator<Item = &FileState> {
+        self.files.iter().map(|file| &file.state)
+    }
+}
+
+
+/// Render the reports of all watched files followed by a summary.
+fn render<'state, I>(states: I, clear: bool, writer: &mut dyn Write) -> Result<()>
+where
+    I: IntoIterator<Item = &'state FileState>,
+{
+    if clear {
+        write!(writer, "{CLEAR}")?;
+    }
+
+    let mut count = 0;
+    let mut findings = 0;
+    let mut files = 0;
+    for state in states {
+        let () = writer.write_all(&state.report)?;
+        count += 1;
+        findings += state.findings;
+        files += usize::from(state.findings > 0);
+    }
+
+    writeln!(
+        writer,
+        "{findings} finding(s) in {files} of {count} file(s); watching for changes..."
+    )?;
+    let () = writer.flush()?;
+    Ok(())
+}
+
+
+/// Collect the paths affected by `event` into `changed`.
+fn changed_paths(event: &Event, changed: &mut BTreeSet<PathBuf>) {
+    if event.kind.is_access() {
+        return
+    }
+
+    let () = changed.extend(event.paths.iter().cloned());
+}
+
+
+/// Lint `paths` using `lint` and re-lint individual files whenever
+/// they change.
+///
+/// `paths` may refer to files or directories. Directories are expanded
+/// to the `.bpf.c` files they (recursively) contain and are watched for
+/// files being created or removed. `lint` is expected to write the
+/// report for the given file to the provided writer and to return the
+/// number of findings. Files are monitored via their parent
+/// directories, so that changes are picked up even if an editor
+/// replaces a file instead of modifying it in place.
+///
+/// This function only returns on error.
+pub fn watch<F>(paths: &[PathBuf], mut lint: F, clear: bool, writer: &mut dyn Write) -> Result<()>
+where
+    F: FnMut(&Path, &mut dyn Write) -> Result<usize>,
+{
+    let (sender, receiver) = channel();
+    let mut watcher = recommended_watcher(sender).context("failed to create file watcher")?;
+    let cwd = current_dir().context("failed to retrieve current working directory")?;
+
+    let mut files = Files::new(paths, &cwd, &mut lint)?;
+
+    let mut watched = HashSet::new();
+    for (_dir, absolute) in &files.dirs {
+        if watched.insert(absolute.clone()) {
+            let () = watcher
+                .watch(absolute, RecursiveMode::Recursive)
+                .with_context(|| format!("failed to watch `{}`", absolute.display()))?;
+        }
+    }
+    for file in files.files.iter().filter(|file| !file.discovered) {
+        let dir = file.absolute.parent().unwrap_or(&cwd).to_path_buf();
+        if watched.insert(dir.clone()) {
+            let () = watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("failed to watch `{}`", dir.display()))?;
+        }
+    }
+
+    let () = render(files.states(), clear, writer)?;
+
+    loop {
+        let mut changed = BTreeSet::new();
+        let mut timeout = None;
+        loop {
+            let result = if let Some(timeout) = timeout {
+                match receiver.recv_timeout(timeout) {
+                    Ok(result) => result,
+                    Err(..) => break,
+                }
+            } else {
+                receiver
+                    .recv()
+                    .context("file watcher unexpectedly disconnected")?
+            };
+
+            match result {
+                Ok(event) => {
+                    debug!("received file system event: {event:?}");
+                    let () = changed_paths(&event, &mut changed);
+                },
+                Err(err) => warn!("failed to watch for file changes: {err}"),
+            }
+            timeout = Some(DEBOUNCE);
+        }
+
+        if files.update(&changed, &cwd, &mut lint) {
+            let () = render(files.states(), clear, writer)?;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::create_dir_all;
+    use std::fs::remove_file;
+    use std::fs::write;
+
+    use anyhow::anyhow;
+
+    use notify::EventKind;
+    use notify::event::AccessKind;
+    use notify::event::ModifyKind;
+
+    use tempfile::tempdir;
+
+
+    /// Check that the final report contains all per-file reports and a
+    /// summary.
+    #[test]
+    fn report_rendering() {
+        let mut lint = |path: &Path, writer: &mut dyn Write| {
+            if path == Path::new("missing") {
+                return Err(anyhow!("file not found"))
+            }
+            writeln!(writer, "report for {}", path.display())?;
+            Ok(2)
+        };
+        let states = [
+            FileState::new(&mut lint, Path::new("foo")),
+            FileState::new(&mut lint, Path::new("missing")),
+            FileState::new(&mut lint, Path::new("bar")),
+        ];
+
+        let mut output = Vec::new();
+        let () = render(&states, false, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = "report for foo\n\
+                        error: file not found\n\
+                        report for bar\n\
+                        4 finding(s) in 2 of 3 file(s); watching for changes...\n";
+        assert_eq!(output, expected);
+
+        let mut output = Vec::new();
+        let () = render(&states, true, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with(CLEAR), "{output}");
+    }
+
+    /// Make sure that access events are ignored.
+    #[test]
+    fn changed_path_detection() {
+        let mut changed = BTreeSet::new();
+        let event = Event::new(EventKind::Modify(ModifyKind::Any))
+            .add_path(PathBuf::from("/foo/a.bpf.c"))
+            .add_path(PathBuf::from("/foo/c.bpf.c"));
+        let () = changed_paths(&event, &mut changed);
+        assert_eq!(
+            changed,
+            BTreeSet::from([PathBuf::from("/foo/a.bpf.c"), PathBuf::from("/foo/c.bpf.c")])
+        );
+
+        let mut changed = BTreeSet::new();
+        let event =
+            Event::new(EventKind::Access(AccessKind::Any)).add_path(PathBuf::from("/foo/b.bpf.c"));
+        let () = changed_paths(&event, &mut changed);
+        assert!(changed.is_empty());
+    }
+
+    /// Check that directory arguments are expanded to the `.bpf.c`
+    /// files they contain and that files created in or removed from
+    /// them later on are picked up.
+    #[test]
+    fn directory_watching() {
+        let dir = tempdir().unwrap();
+        let cwd = dir.path();
+        let () = create_dir_all(cwd.join("src/sub")).unwrap();
+        let () = write(cwd.join("src/b.bpf.c"), "").unwrap();
+        let () = write(cwd.join("src/sub/a.bpf.c"), "").unwrap();
+        let () = write(cwd.join("src/ignored.c"), "").unwrap();
+        let () = write(cwd.join("main.bpf.c"), "").unwrap();
+
+        let mut linted = Vec::new();
+        let mut lint = |path: &Path, writer: &mut dyn Write| {
+            let () = linted.push(path.to_path_buf());
+            writeln!(writer, "report for {}", path.display())?;
+            Ok(1)
+        };
+        let paths = [PathBuf::from("src"), PathBuf::from("main.bpf.c")];
+        let mut files = Files::new(&paths, cwd, &mut lint).unwrap();
+        let expected = ["src/b.bpf.c", "src/sub/a.bpf.c", "main.bpf.c"];
+        let paths = files
+            .files
+            .iter()
+            .map(|file| file.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, expected.map(PathBuf::from));
+
+        // Unrelated changes are ignored.
+        let () = write(cwd.join("src/ignored.c"), "int x;").unwrap();
+        let changed = BTreeSet::from([cwd.join("src/ignored.c"), cwd.join("other.bpf.c")]);
+        assert!(!files.update(&changed, cwd, &mut lint));
+
+        // A new file in a (new) sub-directory gets added and a
+        // modified one re-linted.
+        let () = create_dir_all(cwd.join("src/new")).unwrap();
+        let () = write(cwd.join("src/new/c.bpf.c"), "").unwrap();
+        let changed = BTreeSet::from([cwd.join("src/new"), cwd.join("main.bpf.c")]);
+        assert!(files.update(&changed, cwd, &mut lint));
+        let paths = files
+            .files
+            .iter()
+            .map(|file| file.path.clone())
+            .collect::<Vec<_>>();
+        let expected = [
+            "src/b.bpf.c",
+            "src/sub/a.bpf.c",
+            "main.bpf.c",
+            "src/new/c.bpf.c",
+        ];
+        assert_eq!(paths, expected.map(PathBuf::from));
+
+        // A removed file is dropped.
+        let () = remove_file(cwd.join("src/b.bpf.c")).unwrap();
+        let changed = BTreeSet::from([cwd.join("src/b.bpf.c")]);
+        assert!(files.update(&changed, cwd, &mut lint));
+        assert_eq!(files.files.len(), 3);
+
+        let mut output = Vec::new();
+        let () = render(files.states(), false, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = "report for src/sub/a.bpf.c\n\
+                        report for main.bpf.c\n\
+                        report for src/new/c.bpf.c\n\
+                        3 finding(s) in 3 of 3 file(s); watching for changes...\n";
+        assert_eq!(output, expected);
+
+        let expected = [
+            "src/b.bpf.c",
+            "src/sub/a.bpf.c",
+            "main.bpf.c",
+            "main.bpf.c",
+            "src/new/c.bpf.c",
+        ];
+        assert_eq!(linted, expected.map(PathBuf::from));
+    }
+}
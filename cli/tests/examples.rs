@@ -0,0 +1,148 @@
+//! Tests running `bpflinter` on the examples shipped with the project.
+
+use std::fs::read;
+use std::fs::read_dir;
+use std::fs::read_to_string;
+use std::fs::write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde_json::Value;
+
+use tempfile::tempdir;
+
+
+/// Retrieve the paths of all examples, in sorted order.
+fn examples() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples");
+    let mut examples = read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    let () = examples.sort();
+    assert!(!examples.is_empty());
+    examples
+}
+
+
+/// Make sure that all examples lint without errors.
+///
+/// In particular, valid BPF C code the parser does not fully understand
+/// (such as `SEC()` attributes on declarations) must not cause syntax
+/// errors to be reported by default.
+#[test]
+fn examples_linting() {
+    let examples = examples();
+    let output = Command::new(env!("CARGO_BIN_EXE_bpflinter"))
+        .args(["--no-cache", "--fail-on", "error", "--format", "json"])
+        .args(&examples)
+        .output()
+        .unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let report = serde_json::from_slice::<Value>(&output.stdout).unwrap();
+    let matches = report["matches"].as_array().unwrap();
+    assert!(
+        matches.iter().all(|m| m["lint_name"] != "syntax-error"),
+        "{matches:#?}"
+    );
+    assert_eq!(report["summary"]["syntax_errors"], 0);
+}
+
+/// Check that reports in multiple formats can be written to files in a
+/// single run, linting each file only once.
+#[test]
+fn multi_format_reporting() {
+    let examples = examples();
+    let dir = tempdir().unwrap();
+    let json = dir.path().join("report.json");
+    let sarif = dir.path().join("report.sarif");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpflinter"))
+        .args(["--no-cache", "--fail-on", "error", "-vv", "--format"])
+        .arg(format!("json={}", json.display()))
+        .arg("--format")
+        .arg(format!("sarif={}", sarif.display()))
+        .args(&examples)
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "{stderr}");
+    assert!(output.stdout.is_empty());
+
+    for example in &examples {
+        let linting = format!("linting `{}`", example.display());
+        assert_eq!(stderr.matches(&linting).count(), 1, "{stderr}");
+    }
+
+    let json = serde_json::from_slice::<Value>(&read(json).unwrap()).unwrap();
+    let matches = json["matches"].as_array().unwrap();
+    assert!(!matches.is_empty());
+
+    let sarif = serde_json::from_slice::<Value>(&read(sarif).unwrap()).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), matches.len());
+    for (result, m) in results.iter().zip(matches) {
+        assert_eq!(result["ruleId"], m["lint_name"]);
+    }
+}
+
+/// Check that `--fix` applies suggested fixes to files in place.
+#[test]
+fn fix_application() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("fix.bpf.c");
+    let code = r#"SEC("tp_btf/sched_switch")
+int handle__sched_switch(u64 *ctx)
+{
+    struct task_struct *prev = (struct task_struct *)ctx[1];
+    struct event event = {0};
+    bpf_probe_read(event.comm, TASK_COMM_LEN, prev->comm);
+    return 0;
+}
+"#;
+    let () = write(&path, code).unwrap();
+
+    let lint = || {
+        Command::new(env!("CARGO_BIN_EXE_bpflinter"))
+            .args(["--no-cache", "--format", "json"])
+            .arg(&path)
+            .output()
+            .unwrap()
+    };
+
+    let output = lint();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    let report = serde_json::from_slice::<Value>(&output.stdout).unwrap();
+    let matches = report["matches"].as_array().unwrap();
+    assert_eq!(matches.len(), 1, "{matches:#?}");
+    assert_eq!(matches[0]["fix"], "bpf_probe_read_kernel");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bpflinter"))
+        .args(["--no-cache", "--fix"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "{stderr}");
+    assert_eq!(
+        read_to_string(&path).unwrap(),
+        code.replace("bpf_probe_read(", "bpf_probe_read_kernel(")
+    );
+
+    // Linting the fixed file no longer reports anything.
+    let output = lint();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "{stderr}");
+    let report = serde_json::from_slice::<Value>(&output.stdout).unwrap();
+    assert_eq!(report["matches"], Value::Array(Vec::new()));
+}
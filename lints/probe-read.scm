@@ -6,4 +6,6 @@
                   (expression)
                )
     (#set! "message" "bpf_probe_read() is deprecated and replaced by bpf_probe_user() and bpf_probe_kernel(); refer to bpf-helpers(7)")
+    (#set! "category" "deprecated")
+    (#set! "fix" "bpf_probe_read_kernel")
 )
@@ -3,4 +3,5 @@
     arg1: (identifier) @__arg1 (#any-of? @__arg1 "key_size" "value_size")
     (sizeof_expression)
     (#set! "message" "__uint(<a>_size, sizeof(<b>)) does not contain potentially relevant type information, consider using __type(<a>, <b>) instead")
+    (#set! "category" "style")
 ) @call
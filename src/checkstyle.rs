@@ -0,0 +1,158 @@
+use std::io;
+
+use anyhow::Result;
+
+use crate::LintMatch;
+use crate::Severity;
+use crate::reporter::Reporter;
+use crate::reporter::SourceFile;
+use crate::xml::escape;
+
+
+/// Map `severity` to the corresponding Checkstyle severity.
+fn checkstyle_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Note => "info",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+
+/// A [`Reporter`] producing Checkstyle XML reports.
+///
+/// Each linted file is represented by a `<file>` element, containing
+/// an `<error>` element for each of its matches. Locations are
+/// reported as 1-based lines and columns.
+///
+/// # Example
+/// ```xml
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <checkstyle version="4.3">
+///   <file name="example.bpf.c">
+///     <error line="43" column="5" severity="warning" message="bpf_probe_read() is deprecated" source="bpflint.probe-read"/>
+///   </file>
+/// </checkstyle>
+/// ```
+#[derive(Debug)]
+pub struct CheckstyleReporter<W> {
+    /// The writer to which to write the report.
+    writer: W,
+}
+
+impl<W> CheckstyleReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`CheckstyleReporter`] writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Reporter for CheckstyleReporter<W>
+where
+    W: io::Write,
+{
+    fn begin_run(&mut self) -> Result<()> {
+        writeln!(self.writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(self.writer, r#"<checkstyle version="4.3">"#)?;
+        Ok(())
+    }
+
+    fn begin_file(&mut self, file: &SourceFile<'_>) -> Result<()> {
+        let name = file.path().display().to_string();
+        writeln!(self.writer, r#"  <file name="{}">"#, escape(&name))?;
+        Ok(())
+    }
+
+    fn report_match(&mut self, _file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let LintMatch {
+            lint_name,
+            message,
+            severity,
+            range,
+            fix: _,
+        } = r#match;
+
+        writeln!(
+            self.writer,
+            r#"    <error line="{}" column="{}" severity="{}" message="{}" source="bpflint.{}"/>"#,
+            range.start_point.row + 1,
+            range.start_point.col + 1,
+            checkstyle_severity(*severity),
+            escape(message),
+            escape(lint_name),
+        )?;
+        Ok(())
+    }
+
+    fn end_file(&mut self, _file: &SourceFile<'_>) -> Result<()> {
+        writeln!(self.writer, "  </file>")?;
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        writeln!(self.writer, "</checkstyle>")?;
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+
+
+    /// Check that we produce the expected Checkstyle report.
+    #[test]
+    fn checkstyle_reporting() {
+        let m = LintMatch {
+            lint_name: "probe-read".to_string(),
+            message: "bpf_probe_read() is <deprecated>".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 160..174,
+                start_point: Point { row: 6, col: 4 },
+                end_point: Point { row: 6, col: 18 },
+            },
+            fix: None,
+        };
+
+        let mut reporter = CheckstyleReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("foo&bar.bpf.c"), b"");
+        let () = reporter.report_file(&file, &[m]).unwrap();
+        let file = SourceFile::new(Path::new("clean.bpf.c"), b"");
+        let () = reporter.report_file(&file, &[]).unwrap();
+        let () = reporter.end_run().unwrap();
+
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        let expected = indoc! { r#"
+          <?xml version="1.0" encoding="UTF-8"?>
+          <checkstyle version="4.3">
+            <file name="foo&amp;bar.bpf.c">
+              <error line="7" column="5" severity="warning" message="bpf_probe_read() is &lt;deprecated&gt;" source="bpflint.probe-read"/>
+            </file>
+            <file name="clean.bpf.c">
+            </file>
+          </checkstyle>
+        "# };
+        assert_eq!(report, expected);
+    }
+}
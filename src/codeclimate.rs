@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::io;
+
+use anyhow::Result;
+
+use crate::LintMatch;
+use crate::Severity;
+use crate::json::escape;
+use crate::reporter::Reporter;
+use crate::reporter::SourceFile;
+
+
+/// Map `severity` to the corresponding Code Climate severity.
+fn codeclimate_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Note => "info",
+        Severity::Warning => "minor",
+        Severity::Error => "major",
+    }
+}
+
+/// Calculate the 64 bit FNV-1a hash of the concatenation of `parts`.
+///
+/// Contrary to the hashers provided by the standard library, the
+/// result is guaranteed to be stable across program versions and
+/// platforms.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    parts
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        })
+}
+
+
+/// A [`Reporter`] producing Code Climate JSON reports, as consumed by
+/// GitLab's Code Quality feature.
+///
+/// Each match is reported as an issue with a fingerprint derived from
+/// the file's path, the lint's name, and the matched text. As such,
+/// fingerprints stay the same when code around the match changes.
+/// Identical matches in the same file are distinguished by their order
+/// of occurrence. Locations are reported as 1-based lines.
+///
+/// # Example
+/// ```json
+/// [
+///   {"type": "issue", "check_name": "probe-read", "description": "bpf_probe_read() is deprecated", "severity": "minor", "fingerprint": "2f3a3a6c1d1e0c4b", "location": {"path": "example.bpf.c", "lines": {"begin": 43, "end": 43}}}
+/// ]
+/// ```
+#[derive(Debug)]
+pub struct CodeClimateReporter<W> {
+    /// The writer to which to write the report.
+    writer: W,
+    /// The number of issues reported so far.
+    count: usize,
+    /// The number of occurrences of each fingerprint in the current
+    /// file.
+    occurrences: HashMap<u64, u64>,
+}
+
+impl<W> CodeClimateReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`CodeClimateReporter`] writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            count: 0,
+            occurrences: HashMap::new(),
+        }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Reporter for CodeClimateReporter<W>
+where
+    W: io::Write,
+{
+    fn begin_run(&mut self) -> Result<()> {
+        self.count = 0;
+        write!(self.writer, "[")?;
+        Ok(())
+    }
+
+    fn begin_file(&mut self, _file: &SourceFile<'_>) -> Result<()> {
+        let () = self.occurrences.clear();
+        Ok(())
+    }
+
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let LintMatch {
+            lint_name,
+            message,
+            severity,
+            range,
+            fix: _,
+        } = r#match;
+
+        let path = file.path().display().to_string();
+        let text = file.code().get(range.bytes.clone()).unwrap_or_default();
+        let hash = fnv1a(&[path.as_bytes(), b"\0", lint_name.as_bytes(), b"\0", text]);
+        let occurrence = self.occurrences.entry(hash).or_default();
+        let fingerprint = fnv1a(&[&hash.to_le_bytes(), &occurrence.to_le_bytes()]);
+        *occurrence += 1;
+
+        let separator = if self.count == 0 { "" } else { "," };
+        self.count += 1;
+        write!(
+            self.writer,
+            r#"{separator}
+  {{"type": "issue", "check_name": "{}", "description": "{}", "severity": "{}", "fingerprint": "{fingerprint:016x}", "location": {{"path": "{}", "lines": {{"begin": {}, "end": {}}}}}}}"#,
+            escape(lint_name),
+            escape(message),
+            codeclimate_severity(*severity),
+            escape(&path),
+            range.start_point.row + 1,
+            range.end_point.row + 1,
+        )?;
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        if self.count > 0 {
+            writeln!(self.writer)?;
+        }
+        writeln!(self.writer, "]")?;
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+
+
+    /// Check that our FNV-1a implementation produces well-known
+    /// results.
+    #[test]
+    fn fnv1a_hashing() {
+        assert_eq!(fnv1a(&[]), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(&[b"a"]), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(&[b"foo", b"bar"]), fnv1a(&[b"foobar"]));
+    }
+
+    /// Check that we produce the expected Code Climate report.
+    #[test]
+    fn codeclimate_reporting() {
+        let code = indoc! { r#"
+          foo();
+          foo();
+          bar();
+        "# };
+        let m = |lint_name: &str, row: usize| LintMatch {
+            lint_name: lint_name.to_string(),
+            message: format!("`{lint_name}` is \"bad\""),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: row * 7..row * 7 + 3,
+                start_point: Point { row, col: 0 },
+                end_point: Point { row, col: 3 },
+            },
+            fix: None,
+        };
+        let matches = [m("foo", 0), m("foo", 1), m("bar", 2)];
+
+        let mut reporter = CodeClimateReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("a.bpf.c"), code.as_bytes());
+        let () = reporter.report_file(&file, &matches).unwrap();
+        let () = reporter.end_run().unwrap();
+
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        let issues = report.lines().collect::<Vec<_>>();
+        assert_eq!(issues.len(), 5, "{report}");
+        assert_eq!(issues[0], "[");
+        assert_eq!(issues[4], "]");
+        assert!(
+            issues[1].starts_with(
+                r#"  {"type": "issue", "check_name": "foo", "description": "`foo` is \"bad\"", "severity": "minor", "fingerprint": ""#
+            ),
+            "{report}"
+        );
+        assert!(
+            issues[3].ends_with(
+                r#"", "location": {"path": "a.bpf.c", "lines": {"begin": 3, "end": 3}}}"#
+            ),
+            "{report}"
+        );
+
+        let fingerprint = |issue: &str| {
+            let (_, rest) = issue.split_once(r#""fingerprint": ""#).unwrap();
+            rest[..16].to_string()
+        };
+        let fingerprints = issues[1..4]
+            .iter()
+            .map(|issue| fingerprint(issue))
+            .collect::<Vec<_>>();
+        // Identical matches are still distinguished.
+        assert_ne!(fingerprints[0], fingerprints[1]);
+        assert_ne!(fingerprints[1], fingerprints[2]);
+
+        // Fingerprints are independent of the location of the match.
+        let code = format!("\n\n{code}");
+        let matches = matches
+            .iter()
+            .map(|m| LintMatch {
+                range: Range {
+                    bytes: m.range.bytes.start + 2..m.range.bytes.end + 2,
+                    start_point: Point {
+                        row: m.range.start_point.row + 2,
+                        ..m.range.start_point
+                    },
+                    end_point: Point {
+                        row: m.range.end_point.row + 2,
+                        ..m.range.end_point
+                    },
+                },
+                ..m.clone()
+            })
+            .collect::<Vec<_>>();
+        let mut reporter = CodeClimateReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("a.bpf.c"), code.as_bytes());
+        let () = reporter.report_file(&file, &matches).unwrap();
+        let () = reporter.end_run().unwrap();
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        let shifted = report
+            .lines()
+            .skip(1)
+            .take(3)
+            .map(fingerprint)
+            .collect::<Vec<_>>();
+        assert_eq!(shifted, fingerprints);
+
+        // An empty report is still a valid JSON array.
+        let mut reporter = CodeClimateReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let () = reporter.end_run().unwrap();
+        assert_eq!(reporter.into_inner(), b"[]\n");
+    }
+}
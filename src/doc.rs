@@ -0,0 +1,213 @@
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::anyhow;
+
+
+/// Check whether `line` consists of at least one `c` and nothing else.
+fn is_underline(line: &str, c: char) -> bool {
+    !line.is_empty() && line.chars().all(|x| x == c)
+}
+
+/// Strip the code fence surrounding a code example.
+fn strip_code_fence(section: &str) -> Option<&str> {
+    let code = section.strip_prefix("```c\n")?;
+    let code = code.strip_suffix("```")?;
+    Some(code.trim_end())
+}
+
+
+/// The parsed documentation of a lint.
+///
+/// Lint documentation is written in Markdown, using the following
+/// structure:
+/// ```markdown
+/// <lint-name>
+/// ===========
+///
+/// <description>
+///
+/// Rationale
+/// ---------
+/// <rationale>
+///
+/// Bad Example
+/// -----------
+/// ```c
+/// <code>
+/// ```
+///
+/// Good Example
+/// ------------
+/// ```c
+/// <code>
+/// ```
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LintDoc {
+    /// A description of what the lint flags.
+    pub description: String,
+    /// The reasoning behind the lint.
+    pub rationale: String,
+    /// An example of code that is flagged by the lint.
+    pub example_bad: String,
+    /// An example of code corresponding to `example_bad` that is not
+    /// flagged.
+    pub example_good: String,
+}
+
+impl LintDoc {
+    /// Parse the documentation `doc` of the lint `lint_name`.
+    pub fn parse(lint_name: &str, doc: &str) -> Result<Self> {
+        let lines = doc.lines().collect::<Vec<_>>();
+        match lines.as_slice() {
+            [title, underline, ..] if is_underline(underline, '=') => {
+                if *title != lint_name {
+                    return Err(anyhow!(
+                        "{lint_name}: documentation title `{title}` does not match lint name"
+                    ))
+                }
+            },
+            _ => return Err(anyhow!("{lint_name}: documentation lacks title")),
+        }
+
+        let mut description = String::new();
+        let mut sections = Vec::<(&str, String)>::new();
+        let mut idx = 2;
+        while idx < lines.len() {
+            let line = lines[idx];
+            if lines
+                .get(idx + 1)
+                .is_some_and(|next| is_underline(next, '-'))
+            {
+                let () = sections.push((line, String::new()));
+                idx += 2;
+                continue
+            }
+
+            let content = match sections.last_mut() {
+                Some((_, content)) => content,
+                None => &mut description,
+            };
+            let () = content.push_str(line);
+            let () = content.push('\n');
+            idx += 1;
+        }
+
+        let section = |heading: &str| {
+            sections
+                .iter()
+                .find(|(title, _)| *title == heading)
+                .map(|(_, content)| content.trim().to_string())
+                .with_context(|| format!("{lint_name}: documentation lacks `{heading}` section"))
+        };
+
+        let rationale = section("Rationale")?;
+        let example_bad = section("Bad Example")?;
+        let example_good = section("Good Example")?;
+        let example = |section: &str, heading: &str| {
+            strip_code_fence(section)
+                .map(str::to_string)
+                .with_context(|| {
+                    format!("{lint_name}: `{heading}` section is not a single C code block")
+                })
+        };
+
+        let slf = Self {
+            description: description.trim().to_string(),
+            example_bad: example(&example_bad, "Bad Example")?,
+            example_good: example(&example_good, "Good Example")?,
+            rationale,
+        };
+        Ok(slf)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+
+    /// Check that we can parse well-formed lint documentation.
+    #[test]
+    fn doc_parsing() {
+        let doc = indoc! { r#"
+          foo
+          ===
+
+          Flags calls to `foo()`.
+          Really.
+
+          Rationale
+          ---------
+          `foo()` is
+          bad.
+
+          Bad Example
+          -----------
+          ```c
+          foo();
+          ```
+
+          Good Example
+          ------------
+          ```c
+          bar();
+          baz();
+          ```
+        "# };
+
+        let doc = LintDoc::parse("foo", doc).unwrap();
+        let expected = LintDoc {
+            description: "Flags calls to `foo()`.\nReally.".to_string(),
+            rationale: "`foo()` is\nbad.".to_string(),
+            example_bad: "foo();".to_string(),
+            example_good: "bar();\nbaz();".to_string(),
+        };
+        assert_eq!(doc, expected);
+    }
+
+    /// Check that malformed lint documentation is rejected.
+    #[test]
+    fn invalid_doc_parsing() {
+        let err = LintDoc::parse("foo", "").unwrap_err();
+        assert_eq!(err.to_string(), "foo: documentation lacks title");
+
+        let err = LintDoc::parse("foo", "bar\n===\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "foo: documentation title `bar` does not match lint name"
+        );
+
+        let err = LintDoc::parse("foo", "foo\n===\n\nFoo.\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "foo: documentation lacks `Rationale` section"
+        );
+
+        let doc = indoc! { r#"
+          foo
+          ===
+          Rationale
+          ---------
+          Because.
+
+          Bad Example
+          -----------
+          foo();
+
+          Good Example
+          ------------
+          ```c
+          bar();
+          ```
+        "# };
+        let err = LintDoc::parse("foo", doc).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "foo: `Bad Example` section is not a single C code block"
+        );
+    }
+}
@@ -0,0 +1,587 @@
+use std::iter;
+use std::ops;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::anyhow;
+
+use tree_sitter::InputEdit;
+use tree_sitter::Query;
+use tree_sitter::Tree;
+
+use crate::INCOMPLETE_RESULTS;
+use crate::LintMatch;
+use crate::LintOpts;
+use crate::LintResults;
+use crate::Point;
+use crate::Range;
+use crate::SYNTAX_ERROR;
+use crate::edit::SuppressionScope;
+use crate::edit::TextEdit;
+use crate::edit::end_point;
+use crate::edit::suppression_edit;
+use crate::lint::QueryLimits;
+use crate::lint::compile_query;
+use crate::lint::file_directives;
+use crate::lint::lint_impl;
+use crate::lint::lints;
+use crate::lint::reparse;
+use crate::lint::sort_matches;
+use crate::lint::syntax_errors;
+
+
+/// Shift `point`, located at or after `old_end`, to account for an
+/// edit ending at `old_end` originally and at `new_end` afterwards.
+fn shift_point(point: Point, old_end: Point, new_end: Point) -> Point {
+    if point.row == old_end.row {
+        Point {
+            row: new_end.row,
+            col: new_end.col + (point.col - old_end.col),
+        }
+    } else {
+        Point {
+            row: point.row - old_end.row + new_end.row,
+            col: point.col,
+        }
+    }
+}
+
+/// Determine the byte ranges of the top-level syntax nodes in `tree`
+/// that intersect any of the `changed` byte ranges.
+///
+/// As comments may disable lints for the node following them, all
+/// nodes up to and including the first non-comment node after each
+/// intersecting one are considered affected as well. Adjacent nodes are
+/// merged into a single range.
+fn affected_ranges(tree: &Tree, changed: &[ops::Range<usize>]) -> Vec<ops::Range<usize>> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let mut ranges = Vec::<ops::Range<usize>>::new();
+    let mut prev_affected = false;
+    let mut carry = false;
+    for node in root.children(&mut cursor) {
+        let bytes = node.byte_range();
+        let changed = changed
+            .iter()
+            .any(|range| range.start <= bytes.end && bytes.start <= range.end);
+        let affected = changed || carry;
+        if affected {
+            match ranges.last_mut() {
+                Some(last) if prev_affected => last.end = bytes.end,
+                _ => ranges.push(bytes),
+            }
+        }
+        prev_affected = affected;
+        carry = changed || (carry && node.kind() == "comment");
+    }
+    ranges
+}
+
+/// Run the lints in `queries` on `code`, restricting reported captures
+/// to `ranges`.
+fn lint(
+    queries: &[(&'static str, Query)],
+    opts: &LintOpts,
+    tree: &Tree,
+    code: &[u8],
+    ranges: &[Option<ops::Range<usize>>],
+) -> Result<Vec<LintMatch>> {
+    let limits = QueryLimits::new(opts)?;
+    let mut results = LintResults::default();
+    for (lint_name, query) in queries {
+        for range in ranges {
+            let () = lint_impl(
+                tree,
+                code,
+                query,
+                lint_name,
+                range.clone(),
+                &limits,
+                &mut results,
+            )?;
+        }
+    }
+    Ok(results.matches)
+}
+
+/// Lint the entirety of `code`.
+fn lint_all(
+    queries: &[(&'static str, Query)],
+    opts: &LintOpts,
+    tree: &Tree,
+    code: &[u8],
+) -> Result<Vec<LintMatch>> {
+    let mut matches = if opts.syntax_errors {
+        syntax_errors(tree)
+    } else {
+        Vec::new()
+    };
+    let () = matches.extend(lint(queries, opts, tree, code, &[None])?);
+    let () = sort_matches(&mut matches);
+    Ok(matches)
+}
+
+/// Retrieve the targets of all `bpflint: disable-file=...` directives
+/// in `code`.
+fn directive_targets(tree: &Tree, code: &[u8]) -> Vec<String> {
+    file_directives(tree, code)
+        .map(|(_, _, target)| target.to_string())
+        .collect()
+}
+
+
+/// A document holding source code that is linted incrementally as it
+/// is being edited, as is useful for editor integrations.
+///
+/// The document keeps the syntax tree of the code around. On every
+/// edit, the code is re-parsed incrementally and lints are only re-run
+/// for top-level items (such as function definitions) affected by the
+/// change. Matches elsewhere are carried over, with their locations
+/// adjusted.
+#[derive(Debug)]
+pub struct Document {
+    /// The current source code.
+    code: Vec<u8>,
+    /// The syntax tree of `code`.
+    tree: Tree,
+    /// The queries of all enabled lints, along with their names.
+    queries: Vec<(&'static str, Query)>,
+    /// The options used for linting.
+    opts: LintOpts,
+    /// The lint matches found in `code`, in source code order.
+    matches: Vec<LintMatch>,
+}
+
+impl Document {
+    /// Create a new [`Document`] for `code`, linting it using the
+    /// default set of lints and the provided options.
+    ///
+    /// [`LintOpts::timings`] is not supported for documents and
+    /// ignored.
+    pub fn new(code: Vec<u8>, opts: LintOpts) -> Result<Self> {
+        let queries = lints::LINTS
+            .iter()
+            .filter(|(lint_name, _)| {
+                !opts
+                    .disabled_lints
+                    .iter()
+                    .any(|disabled| disabled == lint_name)
+            })
+            .map(|(lint_name, lint_src)| {
+                let query = compile_query(lint_src)
+                    .with_context(|| format!("{lint_name}: failed to load lint"))?;
+                Ok((*lint_name, query))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let tree = reparse(&code, None)?;
+
+        let matches = lint_all(&queries, &opts, &tree, &code)?;
+        Ok(Self {
+            code,
+            tree,
+            queries,
+            opts,
+            matches,
+        })
+    }
+
+    /// Apply `edit` to the document and re-lint it, returning the
+    /// updated lint matches.
+    ///
+    /// Both the byte range and the points of `edit` have to refer to
+    /// the code as it was before the edit. If an error is reported, the
+    /// document is left unchanged.
+    pub fn edit(&mut self, edit: &TextEdit) -> Result<&[LintMatch]> {
+        let TextEdit { range, text } = edit;
+        let Range {
+            bytes,
+            start_point,
+            end_point: old_end,
+        } = range;
+        if bytes.start > bytes.end || bytes.end > self.code.len() {
+            return Err(anyhow!(
+                "edit range {bytes:?} is invalid for code of {} bytes",
+                self.code.len()
+            ))
+        }
+
+        // NB: We work on copies of the document's state and only commit
+        //     them once everything succeeded.
+        let new_end = end_point(*start_point, text);
+        let new_end_byte = bytes.start + text.len();
+        let input_edit = InputEdit {
+            start_byte: bytes.start,
+            old_end_byte: bytes.end,
+            new_end_byte,
+            start_position: (*start_point).into(),
+            old_end_position: (*old_end).into(),
+            new_end_position: new_end.into(),
+        };
+        let mut old_tree = self.tree.clone();
+        let () = old_tree.edit(&input_edit);
+        let mut code = self.code.clone();
+        drop(code.splice(bytes.clone(), text.bytes()));
+
+        let tree = reparse(&code, Some(&old_tree))?;
+        let changed = old_tree
+            .changed_ranges(&tree)
+            .map(|range| range.start_byte..range.end_byte)
+            .chain(iter::once(bytes.start..new_end_byte))
+            .collect::<Vec<_>>();
+
+        let lint_all = || lint_all(&self.queries, &self.opts, &tree, &code);
+        // Incomplete results can't be amended reliably and changes to
+        // file-wide directives affect all matches, so start over.
+        let matches = if self
+            .matches
+            .iter()
+            .any(|m| m.lint_name == INCOMPLETE_RESULTS)
+            || directive_targets(&tree, &code) != directive_targets(&self.tree, &self.code)
+        {
+            lint_all()?
+        } else {
+            let ranges = affected_ranges(&tree, &changed);
+            let affected = |m: &LintMatch| {
+                ranges
+                    .iter()
+                    .any(|range| range.contains(&m.range.bytes.start))
+            };
+
+            // Carry over matches outside of the edited range, unless
+            // they are located in a part of the code that we re-lint.
+            let mut matches = self
+                .matches
+                .iter()
+                .filter(|m| m.lint_name != SYNTAX_ERROR)
+                .filter_map(|m| {
+                    if m.range.bytes.end <= bytes.start {
+                        Some(m.clone())
+                    } else if m.range.bytes.start >= bytes.end {
+                        let mut m = m.clone();
+                        let range = &mut m.range;
+                        range.bytes = range.bytes.start - bytes.end + new_end_byte
+                            ..range.bytes.end - bytes.end + new_end_byte;
+                        range.start_point = shift_point(range.start_point, *old_end, new_end);
+                        range.end_point = shift_point(range.end_point, *old_end, new_end);
+                        Some(m)
+                    } else {
+                        None
+                    }
+                })
+                .filter(|m| !affected(m))
+                .collect::<Vec<_>>();
+
+            let ranges = ranges.into_iter().map(Some).collect::<Vec<_>>();
+            let relinted = lint(&self.queries, &self.opts, &tree, &code, &ranges)?;
+            if relinted.iter().any(|m| m.lint_name == INCOMPLETE_RESULTS) {
+                lint_all()?
+            } else {
+                let () = matches.extend(relinted);
+                if self.opts.syntax_errors {
+                    let () = matches.extend(syntax_errors(&tree));
+                }
+                let () = sort_matches(&mut matches);
+                matches
+            }
+        };
+
+        self.code = code;
+        self.tree = tree;
+        self.matches = matches;
+        Ok(&self.matches)
+    }
+
+    /// Compute the edit inserting a comment suppressing reporting of
+    /// `match` for the provided `scope`.
+    ///
+    /// This method is equivalent to [`suppression_edit`][crate::suppression_edit],
+    /// invoked with the document's code and syntax tree.
+    pub fn suppression_edit(
+        &self,
+        r#match: &LintMatch,
+        scope: SuppressionScope,
+    ) -> Option<TextEdit> {
+        suppression_edit(&self.tree, &self.code, r#match, scope)
+    }
+
+    /// Retrieve the document's current source code.
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// Retrieve the lint matches found in the document's current
+    /// source code, in source code order.
+    pub fn matches(&self) -> &[LintMatch] {
+        &self.matches
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::slice;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::lint_opts;
+
+
+    /// Create a [`TextEdit`] replacing `bytes` in `code` with `text`.
+    fn text_edit(code: &[u8], bytes: ops::Range<usize>, text: &str) -> TextEdit {
+        let point = |offset: usize| {
+            let before = &code[..offset];
+            let row = before.iter().filter(|b| **b == b'\n').count();
+            let col = offset
+                - before
+                    .iter()
+                    .rposition(|b| *b == b'\n')
+                    .map_or(0, |idx| idx + 1);
+            Point { row, col }
+        };
+
+        TextEdit {
+            range: Range {
+                bytes: bytes.clone(),
+                start_point: point(bytes.start),
+                end_point: point(bytes.end),
+            },
+            text: text.to_string(),
+        }
+    }
+
+    /// Create a [`TextEdit`] replacing the first occurrence of `old`
+    /// in the document's code with `new`.
+    fn replace(document: &Document, old: &str, new: &str) -> TextEdit {
+        let code = document.code();
+        let start = code
+            .windows(old.len())
+            .position(|window| window == old.as_bytes())
+            .unwrap();
+        text_edit(code, start..start + old.len(), new)
+    }
+
+    /// Convert `matches` into a comparable form.
+    fn summarize(matches: &[LintMatch]) -> Vec<(String, Range)> {
+        matches
+            .iter()
+            .map(|m| (m.lint_name.clone(), m.range.clone()))
+            .collect()
+    }
+
+
+    /// Check that incremental linting produces the same results as
+    /// linting the edited code from scratch.
+    #[test]
+    fn incremental_linting() {
+        let code = indoc! { r#"
+          int a(void) {
+            bpf_probe_read(x, y, z);
+          }
+
+          int b(void) {
+            return 0;
+          }
+        "# };
+        let opts = LintOpts {
+            syntax_errors: true,
+            ..Default::default()
+        };
+        let mut document = Document::new(code.as_bytes().to_vec(), opts.clone()).unwrap();
+        assert_eq!(document.matches().len(), 1);
+
+        let edits = [
+            ("  return 0;", "  bpf_probe_read(x, y, z);\n  return 0;"),
+            ("int b", "/* bpflint: disable=probe-read */\nint b"),
+            ("int a", "\n\nint a"),
+            (
+                "bpf_probe_read(x, y, z);",
+                "foo();\n  bpf_probe_read(x, y, z);",
+            ),
+            ("  foo();\n", ""),
+            ("return 0;", "return 0"),
+            ("disable=probe-read", "disable=other"),
+            ("int a(void)", "void a(int x)"),
+            (
+                "void a(int x)",
+                "/* bpflint: disable-file=probe-read */\nvoid a(int x)",
+            ),
+            ("disable-file=probe-read", "disable-file=other"),
+        ];
+        for (old, new) in edits {
+            let edit = replace(&document, old, new);
+            let matches = summarize(document.edit(&edit).unwrap());
+            let expected = lint_opts(document.code(), &opts).unwrap().matches;
+            assert_eq!(matches, summarize(&expected), "{old:?} -> {new:?}");
+        }
+        assert_eq!(document.matches().len(), 3);
+
+        // Suppress the matches one by one.
+        while let Some(r#match) = document
+            .matches()
+            .iter()
+            .find(|m| m.lint_name != SYNTAX_ERROR)
+        {
+            let edit = document
+                .suppression_edit(r#match, SuppressionScope::Statement)
+                .unwrap();
+            let _matches = document.edit(&edit).unwrap();
+        }
+        let expected = lint_opts(document.code(), &opts).unwrap();
+        assert_eq!(summarize(document.matches()), summarize(&expected.matches));
+        assert_eq!(expected.suppressed, 2);
+
+        // Replacing the entire code works as well.
+        let code = document.code().to_vec();
+        let edit = text_edit(
+            &code,
+            0..code.len(),
+            "int c(void) {\n  bpf_probe_read(x, y, z);\n}\n",
+        );
+        let matches = summarize(document.edit(&edit).unwrap());
+        let expected = lint_opts(document.code(), &opts).unwrap().matches;
+        assert_eq!(matches, summarize(&expected));
+        assert_eq!(matches.len(), 1);
+    }
+
+    /// Check that only top-level items affected by a change are
+    /// re-linted.
+    #[test]
+    fn affected_range_determination() {
+        let code = indoc! { r#"
+          int a(void) {}
+          /* comment */
+          int b(void) {}
+          int c(void) {}
+          int d(void) {}
+          /* first */
+          /* second */
+          int e(void) {}
+          int f(void) {}
+        "# };
+        let tree = reparse(code.as_bytes(), None).unwrap();
+        let offset = |text: &str| code.find(text).unwrap();
+
+        let affected = |changed: ops::Range<usize>| {
+            let ranges = affected_ranges(&tree, slice::from_ref(&changed));
+            assert_eq!(ranges.len(), 1, "{ranges:?}");
+            ranges[0].clone()
+        };
+
+        let range = affected(offset("b(")..offset("b("));
+        assert_eq!(range, offset("int b")..offset("\nint d"));
+
+        // The item following a change is affected as well.
+        let range = affected(offset("comment")..offset("comment"));
+        assert_eq!(range, offset("/*")..offset("\nint c"));
+
+        // So are all items up to the next non-comment one.
+        let range = affected(offset("first")..offset("first"));
+        assert_eq!(range, offset("/* first")..offset("\nint f"));
+
+        let ranges = affected_ranges(&tree, &[offset("e(")..offset("e(") + 1, 0..1]);
+        assert_eq!(
+            ranges,
+            [0..offset("\nint c"), offset("int e")..code.len() - 1]
+        );
+    }
+
+    /// Create a large file containing many functions.
+    #[cfg(feature = "nightly")]
+    fn large_file() -> String {
+        (0..1000)
+            .map(|i| {
+                format!("int fn{i}(void) {{\n  bpf_probe_read(x, y, z);\n  return {i};\n}}\n\n")
+            })
+            .collect()
+    }
+
+    /// Benchmark linting a large file from scratch after a small edit.
+    #[cfg(feature = "nightly")]
+    #[bench]
+    fn bench_edit_full(b: &mut test::Bencher) {
+        let code = large_file();
+        let opts = LintOpts::default();
+
+        let () = b.iter(|| {
+            let results = lint_opts(code.as_bytes(), &opts).unwrap();
+            test::black_box(results)
+        });
+    }
+
+    /// Benchmark incrementally re-linting a large file after a small
+    /// edit.
+    #[cfg(feature = "nightly")]
+    #[bench]
+    fn bench_edit_incremental(b: &mut test::Bencher) {
+        let code = large_file();
+        let mut document = Document::new(code.into_bytes(), LintOpts::default()).unwrap();
+
+        let () = b.iter(|| {
+            // Insert a statement and remove it again, to keep the
+            // document stable across iterations.
+            let edit = replace(&document, "return 500;", "x++;\n  return 500;");
+            let _matches = document.edit(&edit).unwrap();
+            let edit = replace(&document, "x++;\n  ", "");
+            let matches = document.edit(&edit).unwrap();
+            test::black_box(matches.len())
+        });
+    }
+
+    /// Check that invalid edits are rejected.
+    #[test]
+    fn invalid_edit_rejection() {
+        let code = b"int a(void) {}\n".to_vec();
+        let mut document = Document::new(code.clone(), LintOpts::default()).unwrap();
+        let edit = text_edit(&code, 0..code.len(), "");
+        let edit = TextEdit {
+            range: Range {
+                bytes: 0..code.len() + 1,
+                ..edit.range
+            },
+            ..edit
+        };
+        let err = document.edit(&edit).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "edit range 0..16 is invalid for code of 15 bytes"
+        );
+        assert_eq!(document.code(), code);
+    }
+
+    /// Check that a failing edit leaves the document unchanged.
+    #[test]
+    fn failed_edit_atomicity() {
+        let code = indoc! { r#"
+          void f(void) {
+            bpf_probe_read(a, b, c);
+          }
+        "# };
+        let document = Document::new(code.as_bytes().to_vec(), LintOpts::default()).unwrap();
+        let tree = document.tree.root_node().to_sexp();
+        let matches = summarize(document.matches());
+        assert_eq!(matches.len(), 1);
+
+        // A lint lacking a `message` property fails once it matches.
+        let query = compile_query(r#"(call_expression function: (identifier) @f (#eq? @f "foo"))"#)
+            .unwrap();
+        let mut document = Document {
+            queries: vec![("broken", query)],
+            ..document
+        };
+        let edit = replace(&document, "bpf_probe_read", "foo");
+        let _err = document.edit(&edit).unwrap_err();
+        assert_eq!(document.code(), code.as_bytes());
+        assert_eq!(document.tree.root_node().to_sexp(), tree);
+        assert_eq!(summarize(document.matches()), matches);
+
+        // Subsequent edits still apply to the unchanged state.
+        let edit = replace(&document, "b, c", "d, e");
+        let matches = document.edit(&edit).unwrap();
+        assert_eq!(summarize(matches), Vec::new());
+        assert_eq!(document.code(), code.replace("b, c", "d, e").as_bytes());
+    }
+}
@@ -0,0 +1,578 @@
+use std::collections::HashSet;
+use std::str;
+use std::str::FromStr;
+
+use anyhow::Error;
+use anyhow::Result;
+use anyhow::anyhow;
+
+use tracing::warn;
+
+use tree_sitter::Node;
+use tree_sitter::Tree;
+
+use crate::INCOMPLETE_RESULTS;
+use crate::LintMatch;
+use crate::Point;
+use crate::Range;
+use crate::SYNTAX_ERROR;
+use crate::lint::directive_covers;
+use crate::lint::disable_directive;
+use crate::lint::file_directives;
+use crate::lint::preceding_comments;
+
+
+/// An edit to source code, replacing the code in a range with new
+/// text.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TextEdit {
+    /// The code range to replace. An empty range denotes an insertion.
+    pub range: Range,
+    /// The text to replace the range with.
+    pub text: String,
+}
+
+
+/// The syntactic scope to which a suppression comment applies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SuppressionScope {
+    /// The innermost statement or declaration containing the match.
+    #[default]
+    Statement,
+    /// The function definition containing the match.
+    Function,
+    /// The entire file containing the match.
+    File,
+}
+
+impl SuppressionScope {
+    /// Retrieve the textual representation of the scope.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Statement => "statement",
+            Self::Function => "function",
+            Self::File => "file",
+        }
+    }
+
+    /// Check whether `node` constitutes the scope.
+    fn contains(&self, node: &Node<'_>) -> bool {
+        match self {
+            Self::Statement => {
+                let kind = node.kind();
+                (kind.ends_with("_statement") && kind != "compound_statement")
+                    || matches!(kind, "declaration" | "field_declaration")
+                    || node.parent().is_some_and(|parent| {
+                        matches!(parent.kind(), "compound_statement" | "translation_unit")
+                    })
+            },
+            Self::Function => node.kind() == "function_definition",
+            Self::File => node.kind() == "translation_unit",
+        }
+    }
+}
+
+impl FromStr for SuppressionScope {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "statement" => Ok(Self::Statement),
+            "function" => Ok(Self::Function),
+            "file" => Ok(Self::File),
+            _ => Err(anyhow!(
+                "invalid suppression scope `{s}` (expected one of `statement`, `function`, `file`)"
+            )),
+        }
+    }
+}
+
+
+/// Compute the point at which `text` ends, if inserted at `start`.
+pub(crate) fn end_point(start: Point, text: &str) -> Point {
+    match text.rfind('\n') {
+        Some(idx) => Point {
+            row: start.row + text.matches('\n').count(),
+            col: text.len() - idx - 1,
+        },
+        None => Point {
+            row: start.row,
+            col: start.col + text.len(),
+        },
+    }
+}
+
+/// Create the edit inserting `comment` right in front of `node`.
+///
+/// If `node` is the first thing on its line, the comment is placed on
+/// a line of its own, indented the same way. Otherwise it is inserted
+/// inline.
+fn insert_before(code: &[u8], node: &Node<'_>, comment: &str) -> TextEdit {
+    let start = node.start_byte();
+    let point = Point::from(node.start_position());
+    let line_start = start - point.col;
+    let indent = &code[line_start..start];
+
+    let (offset, point, text) = if indent.iter().all(|b| matches!(b, b' ' | b'\t')) {
+        let newline = match code[start..].iter().position(|b| *b == b'\n') {
+            Some(idx) if idx > 0 && code[start + idx - 1] == b'\r' => "\r\n",
+            _ => "\n",
+        };
+        // SANITY: The indentation consists of ASCII characters only.
+        let indent = str::from_utf8(indent).unwrap();
+        let point = Point {
+            row: point.row,
+            col: 0,
+        };
+        (line_start, point, format!("{indent}{comment}{newline}"))
+    } else {
+        (start, point, format!("{comment} "))
+    };
+
+    TextEdit {
+        range: Range {
+            bytes: offset..offset,
+            start_point: point,
+            end_point: point,
+        },
+        text,
+    }
+}
+
+/// Compute the edit inserting a comment suppressing reporting of
+/// `match` in `code`, with syntax tree `tree`, for the provided
+/// `scope`.
+///
+/// The comment is placed in front of the innermost syntax node forming
+/// `scope`, as recognized by the lookup of `bpflint: disable=...`
+/// directives. For [`SuppressionScope::File`], a `bpflint:
+/// disable-file=...` directive is placed in front of the first
+/// top-level item instead. If a suitable directive exists already, the
+/// lint is added to it. `None` is returned if the match is not
+/// contained in a node forming `scope`, if the directive covers the
+/// lint already, or if the match can't be suppressed at all (e.g.,
+/// because it reports a syntax error).
+///
+/// `tree` can be created using [`parse`][crate::parse].
+pub fn suppression_edit(
+    tree: &Tree,
+    code: &[u8],
+    r#match: &LintMatch,
+    scope: SuppressionScope,
+) -> Option<TextEdit> {
+    let LintMatch {
+        lint_name, range, ..
+    } = r#match;
+
+    // Pseudo-lints are not subject to suppression and neither are
+    // matches not referring to any code.
+    if [SYNTAX_ERROR, INCOMPLETE_RESULTS].contains(&lint_name.as_str()) || range.bytes.is_empty() {
+        return None
+    }
+
+    if scope == SuppressionScope::File {
+        return file_suppression_edit(tree, code, lint_name)
+    }
+
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(range.bytes.start, range.bytes.end)?;
+    let node = loop {
+        if scope.contains(&node) {
+            break node
+        }
+        node = node.parent()?;
+    };
+
+    // If the node is preceded by a directive already, we add the lint
+    // to it instead of inserting another comment.
+    for comment in preceding_comments(&node) {
+        let text = str::from_utf8(&code[comment.byte_range()]).unwrap_or_default();
+        if let Some(target) = disable_directive(text) {
+            if directive_covers(target, lint_name) {
+                return None
+            }
+            return Some(extend_directive(&comment, text, target, lint_name))
+        }
+    }
+
+    let comment = format!("/* bpflint: disable={lint_name} */");
+    Some(insert_before(code, &node, &comment))
+}
+
+/// Compute the edit suppressing `lint_name` in the entire file with
+/// syntax tree `tree` of `code`.
+///
+/// Existing `bpflint: disable-file=...` directives are extended. New
+/// ones are placed in front of the first top-level item that is not a
+/// comment, so that leading license headers and the like stay in place.
+fn file_suppression_edit(tree: &Tree, code: &[u8], lint_name: &str) -> Option<TextEdit> {
+    if let Some((node, comment, target)) = file_directives(tree, code).next() {
+        if file_directives(tree, code).any(|(_, _, target)| directive_covers(target, lint_name)) {
+            return None
+        }
+        return Some(extend_directive(&node, comment, target, lint_name))
+    }
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let node = root
+        .children(&mut cursor)
+        .find(|node| node.kind() != "comment")?;
+    let comment = format!("/* bpflint: disable-file={lint_name} */");
+    Some(insert_before(code, &node, &comment))
+}
+
+/// Create the edit adding `lint_name` to the disable directive with
+/// `target` contained in `comment`, the text of `node`.
+fn extend_directive(node: &Node<'_>, comment: &str, target: &str, lint_name: &str) -> TextEdit {
+    // `target` is a sub-slice of `comment`, so we can infer its
+    // location from their addresses.
+    let end = target.as_ptr() as usize - comment.as_ptr() as usize + target.len();
+    let offset = node.start_byte() + end;
+    let point = end_point(Point::from(node.start_position()), &comment[..end]);
+    let text = if target.is_empty() {
+        lint_name.to_string()
+    } else {
+        format!(",{lint_name}")
+    };
+
+    TextEdit {
+        range: Range {
+            bytes: offset..offset,
+            start_point: point,
+            end_point: point,
+        },
+        text,
+    }
+}
+
+
+/// Apply `edits` to `code`, returning the resulting code.
+///
+/// Edits are applied in order of their position in the code, with
+/// insertions at the same position being applied in the order provided.
+/// Duplicate edits are applied only once and edits overlapping
+/// previously applied ones are skipped.
+pub fn apply_edits(code: &[u8], edits: &[TextEdit]) -> Vec<u8> {
+    let mut seen = HashSet::new();
+    let mut edits = edits
+        .iter()
+        .filter(|edit| seen.insert(*edit))
+        .collect::<Vec<_>>();
+    let () = edits.sort_by_key(|edit| (edit.range.bytes.start, edit.range.bytes.end));
+
+    let mut result = Vec::with_capacity(code.len());
+    let mut prev = None::<&TextEdit>;
+    for edit in edits {
+        let bytes = &edit.range.bytes;
+        if bytes.end > code.len() || bytes.start > bytes.end {
+            warn!("skipping edit of out-of-bounds range {bytes:?}");
+            continue
+        }
+
+        if let Some(prev) = prev {
+            let prev = &prev.range.bytes;
+            if bytes.start < prev.end {
+                warn!("skipping edit of range {bytes:?} conflicting with edit of {prev:?}");
+                continue
+            }
+        }
+
+        let copied = prev.map(|prev| prev.range.bytes.end).unwrap_or_default();
+        let () = result.extend_from_slice(&code[copied..bytes.start]);
+        let () = result.extend_from_slice(edit.text.as_bytes());
+        prev = Some(edit);
+    }
+    let copied = prev.map(|prev| prev.range.bytes.end).unwrap_or_default();
+    let () = result.extend_from_slice(&code[copied..]);
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ops;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::LintOpts;
+    use crate::lint;
+    use crate::lint_opts;
+    use crate::parse;
+
+
+    /// Suppress the single `probe-read` match in `code` in `scope`,
+    /// returning the edited code.
+    fn suppress(code: &str, scope: SuppressionScope) -> Option<String> {
+        let matches = lint(code.as_bytes()).unwrap();
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        let tree = parse(code.as_bytes()).unwrap();
+        let edit = suppression_edit(&tree, code.as_bytes(), &matches[0], scope)?;
+        let code = apply_edits(code.as_bytes(), &[edit]);
+        Some(String::from_utf8(code).unwrap())
+    }
+
+    /// Check that suppression comments are inserted in front of the
+    /// correct node and are picked up by subsequent lint runs.
+    #[test]
+    fn suppression_insertion() {
+        let code = indoc! { r#"
+          /* Read some data. */
+          int handler(void) {
+          	if (x)
+          		return bpf_probe_read(a, b, c);
+          	return 0;
+          }
+        "# };
+
+        let edited = suppress(code, SuppressionScope::Statement).unwrap();
+        let expected = indoc! { r#"
+          /* Read some data. */
+          int handler(void) {
+          	if (x)
+          		/* bpflint: disable=probe-read */
+          		return bpf_probe_read(a, b, c);
+          	return 0;
+          }
+        "# };
+        assert_eq!(edited, expected);
+        let results = lint_opts(edited.as_bytes(), &LintOpts::default()).unwrap();
+        assert_eq!(results.matches.len(), 0, "{results:?}");
+        assert_eq!(results.suppressed, 1);
+
+        let edited = suppress(code, SuppressionScope::Function).unwrap();
+        let expected = indoc! { r#"
+          /* Read some data. */
+          /* bpflint: disable=probe-read */
+          int handler(void) {
+          	if (x)
+          		return bpf_probe_read(a, b, c);
+          	return 0;
+          }
+        "# };
+        assert_eq!(edited, expected);
+        let results = lint_opts(edited.as_bytes(), &LintOpts::default()).unwrap();
+        assert_eq!(results.matches.len(), 0, "{results:?}");
+        assert_eq!(results.suppressed, 1);
+
+        let edited = suppress(code, SuppressionScope::File).unwrap();
+        let expected = indoc! { r#"
+          /* Read some data. */
+          /* bpflint: disable-file=probe-read */
+          int handler(void) {
+          	if (x)
+          		return bpf_probe_read(a, b, c);
+          	return 0;
+          }
+        "# };
+        assert_eq!(edited, expected);
+        let results = lint_opts(edited.as_bytes(), &LintOpts::default()).unwrap();
+        assert_eq!(results.matches.len(), 0, "{results:?}");
+        assert_eq!(results.suppressed, 1);
+
+        // Statements not starting a line get an inline comment and
+        // line endings are preserved.
+        let code = "int handler(void) {\r\n  if (x) bpf_probe_read(a, b, c);\r\n}\r\n";
+        let edited = suppress(code, SuppressionScope::Statement).unwrap();
+        assert_eq!(
+            edited,
+            "int handler(void) {\r\n  if (x) /* bpflint: disable=probe-read */ bpf_probe_read(a, b, c);\r\n}\r\n"
+        );
+        assert_eq!(lint(edited.as_bytes()).unwrap().len(), 0);
+
+        let code = "int handler(void) {\r\n  bpf_probe_read(a, b, c);\r\n}\r\n";
+        let edited = suppress(code, SuppressionScope::Statement).unwrap();
+        assert_eq!(
+            edited,
+            "int handler(void) {\r\n  /* bpflint: disable=probe-read */\r\n  bpf_probe_read(a, b, c);\r\n}\r\n"
+        );
+
+        // Directives in effect for the statement already are extended.
+        let code = indoc! { r#"
+          int handler(void) {
+            /* bpflint: disable=other */
+            /* Read some data. */
+            bpf_probe_read(a, b, c);
+          }
+        "# };
+        let edited = suppress(code, SuppressionScope::Statement).unwrap();
+        let expected = indoc! { r#"
+          int handler(void) {
+            /* bpflint: disable=other,probe-read */
+            /* Read some data. */
+            bpf_probe_read(a, b, c);
+          }
+        "# };
+        assert_eq!(edited, expected);
+        assert_eq!(lint(edited.as_bytes()).unwrap().len(), 0);
+
+        let code = indoc! { r#"
+          int handler(void) {
+            bpf_probe_read(a, b, c);
+          }
+          // bpflint: disable-file=other
+        "# };
+        let edited = suppress(code, SuppressionScope::File).unwrap();
+        let expected = indoc! { r#"
+          int handler(void) {
+            bpf_probe_read(a, b, c);
+          }
+          // bpflint: disable-file=other,probe-read
+        "# };
+        assert_eq!(edited, expected);
+        assert_eq!(lint(edited.as_bytes()).unwrap().len(), 0);
+    }
+
+    /// Check that matches of different lints in the same scope can be
+    /// suppressed at once.
+    #[test]
+    fn suppression_combination() {
+        let suppress = |code: &str| {
+            let r#match = lint(code.as_bytes()).unwrap().remove(0);
+            let other = LintMatch {
+                lint_name: "other".to_string(),
+                ..r#match.clone()
+            };
+            let tree = parse(code.as_bytes()).unwrap();
+            let edits = [&r#match, &other].map(|r#match| {
+                suppression_edit(&tree, code.as_bytes(), r#match, SuppressionScope::Statement)
+                    .unwrap()
+            });
+            let edited = apply_edits(code.as_bytes(), &edits);
+            String::from_utf8(edited).unwrap()
+        };
+
+        let code = "int handler(void) {\n  bpf_probe_read(a, b, c);\n}\n";
+        let edited = suppress(code);
+        assert_eq!(
+            edited,
+            "int handler(void) {\n  /* bpflint: disable=probe-read */\n  /* bpflint: disable=other */\n  bpf_probe_read(a, b, c);\n}\n"
+        );
+        assert_eq!(lint(edited.as_bytes()).unwrap().len(), 0);
+
+        let code =
+            "int handler(void) {\n  // bpflint: disable=foo\n  bpf_probe_read(a, b, c);\n}\n";
+        let edited = suppress(code);
+        assert_eq!(
+            edited,
+            "int handler(void) {\n  // bpflint: disable=foo,probe-read,other\n  bpf_probe_read(a, b, c);\n}\n"
+        );
+        assert_eq!(lint(edited.as_bytes()).unwrap().len(), 0);
+    }
+
+    /// Check that we don't produce suppression comments where they
+    /// would not have an effect.
+    #[test]
+    fn suppression_rejection() {
+        // Not inside a function.
+        let code = "int x = bpf_probe_read(a, b, c);\n";
+        assert_eq!(suppress(code, SuppressionScope::Function), None);
+
+        // The lint is disabled for the statement already.
+        let code = "int handler(void) {\n  bpf_probe_read(a, b, c);\n}\n";
+        let r#match = lint(code.as_bytes()).unwrap().remove(0);
+        let code = code.replace("  bpf", "  /* bpflint: disable=all */ bpf");
+        let start = code.find("bpf_probe_read").unwrap();
+        let r#match = LintMatch {
+            range: Range {
+                bytes: start..start + r#match.range.bytes.len(),
+                ..r#match.range
+            },
+            ..r#match
+        };
+        let tree = parse(code.as_bytes()).unwrap();
+        let edit = suppression_edit(
+            &tree,
+            code.as_bytes(),
+            &r#match,
+            SuppressionScope::Statement,
+        );
+        assert_eq!(edit, None);
+        let edit = suppression_edit(&tree, code.as_bytes(), &r#match, SuppressionScope::Function);
+        assert!(edit.is_some());
+
+        // Syntax errors can't be suppressed.
+        let code = "int handler(void) {\n  return 0\n}\n";
+        let opts = LintOpts {
+            syntax_errors: true,
+            ..Default::default()
+        };
+        let matches = lint_opts(code.as_bytes(), &opts).unwrap().matches;
+        assert_eq!(matches[0].lint_name, SYNTAX_ERROR);
+        let tree = parse(code.as_bytes()).unwrap();
+        let edit = suppression_edit(
+            &tree,
+            code.as_bytes(),
+            &matches[0],
+            SuppressionScope::Statement,
+        );
+        assert_eq!(edit, None);
+    }
+
+    /// Check that edits are applied as expected.
+    #[test]
+    fn edit_application() {
+        let edit = |bytes: ops::Range<usize>, text: &str| TextEdit {
+            range: Range {
+                bytes,
+                start_point: Point::default(),
+                end_point: Point::default(),
+            },
+            text: text.to_string(),
+        };
+        let code = b"0123456789";
+
+        assert_eq!(apply_edits(code, &[]), code);
+        let edits = [edit(8..10, "x"), edit(0..0, "a"), edit(2..4, "")];
+        assert_eq!(apply_edits(code, &edits), b"a014567x");
+
+        // Duplicates are applied once, conflicts are skipped.
+        let edits = [
+            edit(2..4, "x"),
+            edit(2..4, "x"),
+            edit(3..5, "y"),
+            edit(4..4, "w"),
+            edit(9..11, "v"),
+        ];
+        assert_eq!(apply_edits(code, &edits), b"01xw456789");
+
+        // Insertions at the same position are all applied, in order.
+        let edits = [edit(2..4, "x"), edit(2..2, "z")];
+        assert_eq!(apply_edits(code, &edits), b"01zx456789");
+        let edits = [edit(1..1, "b"), edit(1..1, "a"), edit(1..1, "b")];
+        assert_eq!(apply_edits(code, &edits), b"0ba123456789");
+    }
+
+    /// Check that suggested fixes are converted into edits.
+    #[test]
+    fn fix_edit_creation() {
+        let r#match = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo".to_string(),
+            severity: Default::default(),
+            range: Range {
+                bytes: 1..3,
+                start_point: Point { row: 0, col: 1 },
+                end_point: Point { row: 0, col: 3 },
+            },
+            fix: None,
+        };
+        assert_eq!(r#match.fix_edit(), None);
+
+        let r#match = LintMatch {
+            fix: Some("bar".to_string()),
+            ..r#match
+        };
+        let edit = r#match.fix_edit().unwrap();
+        assert_eq!(edit.range, r#match.range);
+        assert_eq!(apply_edits(b"afoob", &[edit]), b"abarob");
+    }
+}
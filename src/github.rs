@@ -0,0 +1,197 @@
+use std::borrow::Cow;
+use std::io;
+
+use anyhow::Result;
+
+use crate::LintMatch;
+use crate::Severity;
+use crate::reporter::Reporter;
+use crate::reporter::SourceFile;
+
+
+/// Map `severity` to the corresponding workflow command.
+fn github_command(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Note => "notice",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+/// Escape `text` for use as the data of a workflow command.
+fn escape_data(text: &str) -> Cow<'_, str> {
+    if !text.contains(['%', '\r', '\n']) {
+        return Cow::Borrowed(text)
+    }
+
+    Cow::Owned(
+        text.replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A"),
+    )
+}
+
+/// Escape `text` for use as a property value of a workflow command.
+fn escape_property(text: &str) -> Cow<'_, str> {
+    let text = escape_data(text);
+    if !text.contains([':', ',']) {
+        return text
+    }
+
+    Cow::Owned(text.replace(':', "%3A").replace(',', "%2C"))
+}
+
+
+/// A [`Reporter`] producing GitHub Actions workflow commands, which
+/// are rendered as annotations of the code.
+///
+/// Lines and columns are reported as 1-based numbers, with the end
+/// column being inclusive.
+///
+/// # Example
+/// ```text
+/// ::warning file=example.bpf.c,line=43,col=5,endLine=43,endColumn=18,title=probe-read::bpf_probe_read() is deprecated
+/// ```
+#[derive(Debug)]
+pub struct GitHubReporter<W> {
+    /// The writer to which to write the report.
+    writer: W,
+}
+
+impl<W> GitHubReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`GitHubReporter`] writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Reporter for GitHubReporter<W>
+where
+    W: io::Write,
+{
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let LintMatch {
+            lint_name,
+            message,
+            severity,
+            range,
+            fix: _,
+        } = r#match;
+
+        let path = file.path().display().to_string();
+        // NB: The end point is exclusive, so its zero-based column is
+        //     the 1-based column of the last matched character. Empty
+        //     ranges still cover the start column.
+        let end_col = if range.bytes.is_empty() {
+            range.start_point.col + 1
+        } else {
+            range.end_point.col
+        };
+        writeln!(
+            self.writer,
+            "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}",
+            github_command(*severity),
+            escape_property(&path),
+            range.start_point.row + 1,
+            range.start_point.col + 1,
+            range.end_point.row + 1,
+            end_col,
+            escape_property(lint_name),
+            escape_data(message),
+        )?;
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+
+
+    /// Check that data and property values are escaped correctly.
+    #[test]
+    fn escaping() {
+        assert!(matches!(escape_data("foo: bar"), Cow::Borrowed(_)));
+        assert_eq!(
+            escape_data("100%\r\nfoo: bar, baz"),
+            "100%25%0D%0Afoo: bar, baz"
+        );
+        assert!(matches!(escape_property("foo"), Cow::Borrowed(_)));
+        assert_eq!(escape_property("a:b,c%\n"), "a%3Ab%2Cc%25%0A");
+    }
+
+    /// Check that we produce the expected workflow commands.
+    #[test]
+    fn github_reporting() {
+        let m = |severity, message: &str| LintMatch {
+            lint_name: "probe-read".to_string(),
+            message: message.to_string(),
+            severity,
+            range: Range {
+                bytes: 160..174,
+                start_point: Point { row: 6, col: 4 },
+                end_point: Point { row: 7, col: 18 },
+            },
+            fix: None,
+        };
+        let matches = [
+            m(Severity::Warning, "bpf_probe_read() is deprecated"),
+            m(Severity::Note, "50% done\nsee: foo, bar"),
+            m(Severity::Error, "bad"),
+        ];
+
+        let mut reporter = GitHubReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("src/a,b.bpf.c"), b"");
+        let () = reporter.report_file(&file, &matches).unwrap();
+        let () = reporter.end_run().unwrap();
+
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        let expected = "\
+::warning file=src/a%2Cb.bpf.c,line=7,col=5,endLine=8,endColumn=18,title=probe-read::bpf_probe_read() is deprecated
+::notice file=src/a%2Cb.bpf.c,line=7,col=5,endLine=8,endColumn=18,title=probe-read::50%25 done%0Asee: foo, bar
+::error file=src/a%2Cb.bpf.c,line=7,col=5,endLine=8,endColumn=18,title=probe-read::bad
+";
+        assert_eq!(report, expected);
+
+        // Empty ranges are reported as covering a single column.
+        let m = LintMatch {
+            lint_name: "bogus-file-extension".to_string(),
+            message: "bad extension".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 0..0,
+                start_point: Point { row: 0, col: 0 },
+                end_point: Point { row: 0, col: 0 },
+            },
+            fix: None,
+        };
+        let mut reporter = GitHubReporter::new(Vec::new());
+        let file = SourceFile::new(Path::new("a.c"), b"");
+        let () = reporter.report_file(&file, &[m]).unwrap();
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        let expected = "::warning file=a.c,line=1,col=1,endLine=1,endColumn=1,title=bogus-file-extension::bad extension\n";
+        assert_eq!(report, expected);
+    }
+}
@@ -0,0 +1,522 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::mem;
+use std::ops;
+
+use anyhow::Result;
+
+use tree_sitter::Node;
+use tree_sitter::Parser;
+use tree_sitter_bpf_c::LANGUAGE;
+
+use crate::LintMatch;
+use crate::Severity;
+use crate::report::Opts;
+use crate::report::snippet_rows;
+use crate::reporter::Reporter;
+use crate::reporter::SourceFile;
+use crate::xml::escape;
+
+
+/// The style sheet embedded in the report.
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #1f2328; }
+table.index { border-collapse: collapse; }
+table.index th, table.index td { padding: 0.2em 1em; text-align: left; }
+table.index tr:nth-child(even) { background: #f6f8fa; }
+nav.filters { margin: 1em 0; }
+section.file { margin-top: 2em; }
+div.match { margin: 1em 0 1em 1em; }
+div.match p { margin: 0.2em 0; }
+.location { color: #59636e; font-family: monospace; }
+.severity { font-weight: bold; }
+.severity.error { color: #cf222e; }
+.severity.warning { color: #9a6700; }
+.severity.note { color: #0969da; }
+pre.snippet { background: #f6f8fa; padding: 0.5em; overflow-x: auto; }
+.row { display: inline-block; min-width: 3em; padding-right: 1em; color: #59636e; text-align: right; user-select: none; }
+mark { background: #ffd8b5; }
+.comment { color: #59636e; font-style: italic; }
+.keyword { color: #cf222e; }
+.type { color: #953800; }
+.function { color: #8250df; }
+.string { color: #0a3069; }
+.number { color: #0550ae; }
+"#;
+
+/// The script implementing filtering of matches by lint and severity.
+const SCRIPT: &str = r#"
+const lint = document.getElementById("lint-filter");
+const severity = document.getElementById("severity-filter");
+function update() {
+  for (const file of document.querySelectorAll("section.file")) {
+    let visible = 0;
+    for (const m of file.querySelectorAll("div.match")) {
+      const show = (!lint.value || m.dataset.lint === lint.value) &&
+                   (!severity.value || m.dataset.severity === severity.value);
+      m.hidden = !show;
+      visible += show;
+    }
+    file.hidden = visible === 0;
+  }
+}
+lint.addEventListener("change", update);
+severity.addEventListener("change", update);
+for (const a of document.querySelectorAll("a[data-lint]")) {
+  a.addEventListener("click", (event) => {
+    event.preventDefault();
+    lint.value = a.dataset.lint;
+    update();
+  });
+}
+"#;
+
+
+/// Classify `node` for the purpose of syntax highlighting.
+///
+/// The returned class is also used as the CSS class of the rendered
+/// text.
+fn highlight_class(node: Node<'_>) -> Option<&'static str> {
+    match node.kind() {
+        "comment" => Some("comment"),
+        "string_literal" | "char_literal" | "system_lib_string" => Some("string"),
+        "number_literal" => Some("number"),
+        "primitive_type" | "type_identifier" => Some("type"),
+        "identifier" => {
+            let parent = node.parent()?;
+            let field = match parent.kind() {
+                "call_expression" => "function",
+                "function_declarator" => "declarator",
+                _ => return None,
+            };
+            (parent.child_by_field_name(field) == Some(node)).then_some("function")
+        },
+        kind if !node.is_named() => {
+            let keyword =
+                kind.starts_with('#') || kind.chars().all(|c| c.is_ascii_alphabetic() || c == '_');
+            keyword.then_some("keyword")
+        },
+        _ => None,
+    }
+}
+
+/// Determine the byte ranges of highlighted syntax elements in `code`,
+/// along with their class, in ascending order.
+///
+/// Code that cannot be parsed is not highlighted.
+fn highlights(code: &[u8]) -> Vec<(ops::Range<usize>, &'static str)> {
+    let mut parser = Parser::new();
+    if parser.set_language(&LANGUAGE.into()).is_err() {
+        return Vec::new()
+    }
+    let Some(tree) = parser.parse(code, None) else {
+        return Vec::new()
+    };
+
+    let mut highlights = Vec::new();
+    let mut cursor = tree.walk();
+    let mut descend = true;
+    loop {
+        let node = cursor.node();
+        if descend {
+            if let Some(class) = highlight_class(node) {
+                if !node.byte_range().is_empty() {
+                    let () = highlights.push((node.byte_range(), class));
+                }
+            } else if cursor.goto_first_child() {
+                continue
+            }
+        }
+
+        if cursor.goto_next_sibling() {
+            descend = true;
+        } else if cursor.goto_parent() {
+            descend = false;
+        } else {
+            break highlights
+        }
+    }
+}
+
+/// Render `text` as HTML.
+///
+/// Bytes not forming valid UTF-8 are rendered as `\xNN` escape
+/// sequences, just as in terminal reports.
+fn render(text: &[u8], rendered: &mut String) {
+    for chunk in text.utf8_chunks() {
+        let () = rendered.push_str(&escape(chunk.valid()));
+        for byte in chunk.invalid() {
+            // SANITY: Writing to a `String` never fails.
+            let () = write!(rendered, "\\x{byte:02X}").unwrap();
+        }
+    }
+}
+
+/// Render the line of code spanning `line` (in bytes) as HTML,
+/// applying syntax highlighting and marking the bytes in `marked`.
+fn render_line(
+    code: &[u8],
+    line: ops::Range<usize>,
+    highlights: &[(ops::Range<usize>, &'static str)],
+    marked: &ops::Range<usize>,
+    rendered: &mut String,
+) {
+    let first = highlights.partition_point(|(range, _)| range.end <= line.start);
+    let highlights = &highlights[first..];
+    let last = highlights.partition_point(|(range, _)| range.start < line.end);
+    let highlights = &highlights[..last];
+
+    let mut bounds = highlights
+        .iter()
+        .flat_map(|(range, _)| [range.start, range.end])
+        .chain([line.start, line.end, marked.start, marked.end])
+        .filter(|idx| line.contains(idx) || *idx == line.end)
+        .collect::<Vec<_>>();
+    let () = bounds.sort_unstable();
+    let () = bounds.dedup();
+
+    for bytes in bounds.windows(2) {
+        let (start, end) = (bytes[0], bytes[1]);
+        let class = highlights
+            .iter()
+            .find(|(range, _)| range.contains(&start))
+            .map(|(_, class)| *class);
+        let mark = marked.contains(&start);
+
+        if mark {
+            let () = rendered.push_str("<mark>");
+        }
+        if let Some(class) = class {
+            // SANITY: Writing to a `String` never fails.
+            let () = write!(rendered, r#"<span class="{class}">"#).unwrap();
+        }
+        let () = render(&code[start..end], rendered);
+        if class.is_some() {
+            let () = rendered.push_str("</span>");
+        }
+        if mark {
+            let () = rendered.push_str("</mark>");
+        }
+    }
+}
+
+
+/// A [`Reporter`] producing a self-contained HTML report.
+///
+/// The report is a single static page, not referencing any external
+/// resources. It contains an index of all reported lints along with
+/// their number of matches, as well as a section for each file with
+/// matches. Each match is accompanied by a syntax highlighted code
+/// snippet, with the match's range marked inline. Matches can be
+/// filtered by lint and by severity.
+///
+/// Snippets include context lines as configured in the provided
+/// [`Opts`] and tabs are displayed with its tab width. Locations are
+/// reported as 1-based lines and columns, unlike in terminal style
+/// reports, which use zero-based ones.
+///
+/// Because the report starts with aggregate counts, it is only written
+/// once the run has ended.
+#[derive(Debug)]
+pub struct HtmlReporter<W> {
+    /// The writer to which to write the report.
+    writer: W,
+    /// The options used for rendering code snippets.
+    opts: Opts,
+    /// The number of files linted.
+    files: usize,
+    /// The number of matches per lint.
+    lints: BTreeMap<String, usize>,
+    /// The rendered sections of files with matches.
+    sections: Vec<String>,
+    /// The section of the current file.
+    section: String,
+    /// The syntax highlights of the current file.
+    highlights: Vec<(ops::Range<usize>, &'static str)>,
+}
+
+impl<W> HtmlReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`HtmlReporter`] writing to `writer`.
+    pub fn new(writer: W, opts: Opts) -> Self {
+        Self {
+            writer,
+            opts,
+            files: 0,
+            lints: BTreeMap::new(),
+            sections: Vec::new(),
+            section: String::new(),
+            highlights: Vec::new(),
+        }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Render the code snippet for `match` in `file`.
+    fn render_snippet(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) {
+        let code = file.code();
+        let index = file.index();
+        let rows = snippet_rows(r#match, index, &self.opts);
+
+        let () = self.section.push_str(r#"<pre class="snippet"><code>"#);
+        for row in rows {
+            let Some(start) = index.line_start(row) else {
+                break
+            };
+            let end = start + index.line(code, row).unwrap_or_default().len();
+            // SANITY: Writing to a `String` never fails.
+            let () = write!(self.section, r#"<span class="row">{}</span>"#, row + 1).unwrap();
+            let () = render_line(
+                code,
+                start..end,
+                &self.highlights,
+                &r#match.range.bytes,
+                &mut self.section,
+            );
+            let () = self.section.push('\n');
+        }
+        let () = self.section.push_str("</code></pre>\n");
+    }
+}
+
+impl<W> Reporter for HtmlReporter<W>
+where
+    W: io::Write,
+{
+    fn begin_run(&mut self) -> Result<()> {
+        self.files = 0;
+        let () = self.lints.clear();
+        let () = self.sections.clear();
+        Ok(())
+    }
+
+    fn begin_file(&mut self, _file: &SourceFile<'_>) -> Result<()> {
+        self.files += 1;
+        self.highlights = Vec::new();
+        let () = self.section.clear();
+        Ok(())
+    }
+
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let LintMatch {
+            lint_name,
+            message,
+            severity,
+            range,
+            fix: _,
+        } = r#match;
+
+        let path = file.path().display().to_string();
+        if self.section.is_empty() {
+            // Only parse the file once we know that it is reported on.
+            self.highlights = highlights(file.code());
+            // SANITY: Writing to a `String` never fails.
+            let () = writeln!(
+                self.section,
+                r#"<section class="file" id="file-{}">"#,
+                self.sections.len()
+            )
+            .unwrap();
+            let () = writeln!(self.section, "<h2>{}</h2>", escape(&path)).unwrap();
+        }
+
+        *self.lints.entry(lint_name.clone()).or_default() += 1;
+
+        let severity = severity.as_str();
+        let lint_name = escape(lint_name);
+        // SANITY: Writing to a `String` never fails.
+        let () = writeln!(
+            self.section,
+            r#"<div class="match" data-lint="{lint_name}" data-severity="{severity}">
+<p class="header"><span class="severity {severity}">{severity}</span>: <strong>[{lint_name}]</strong> {}</p>
+<p class="location">{}:{}:{}</p>"#,
+            escape(message),
+            escape(&path),
+            range.start_point.row + 1,
+            range.start_point.col + 1,
+        )
+        .unwrap();
+
+        if !range.bytes.is_empty() {
+            let () = self.render_snippet(file, r#match);
+        }
+        let () = self.section.push_str("</div>\n");
+        Ok(())
+    }
+
+    fn end_file(&mut self, _file: &SourceFile<'_>) -> Result<()> {
+        if !self.section.is_empty() {
+            let () = self.section.push_str("</section>\n");
+            let () = self.sections.push(mem::take(&mut self.section));
+        }
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        let matches = self.lints.values().sum::<usize>();
+
+        writeln!(self.writer, "<!DOCTYPE html>")?;
+        writeln!(self.writer, r#"<html lang="en">"#)?;
+        writeln!(self.writer, "<head>")?;
+        writeln!(self.writer, r#"<meta charset="utf-8">"#)?;
+        writeln!(self.writer, "<title>bpflint report</title>")?;
+        writeln!(
+            self.writer,
+            "<style>{STYLE}pre.snippet {{ tab-size: {}; }}\n</style>",
+            self.opts.tab_width
+        )?;
+        writeln!(self.writer, "</head>")?;
+        writeln!(self.writer, "<body>")?;
+        writeln!(self.writer, "<h1>bpflint report</h1>")?;
+        writeln!(
+            self.writer,
+            r#"<p class="summary">{matches} match(es) in {} of {} file(s)</p>"#,
+            self.sections.len(),
+            self.files,
+        )?;
+
+        writeln!(self.writer, r#"<nav class="filters">"#)?;
+        writeln!(
+            self.writer,
+            r#"<label>Lint <select id="lint-filter"><option value="">all</option>"#
+        )?;
+        for lint_name in self.lints.keys() {
+            let lint_name = escape(lint_name);
+            writeln!(self.writer, "<option>{lint_name}</option>")?;
+        }
+        writeln!(self.writer, "</select></label>")?;
+        writeln!(
+            self.writer,
+            r#"<label>Severity <select id="severity-filter"><option value="">all</option>"#
+        )?;
+        for severity in [Severity::Error, Severity::Warning, Severity::Note] {
+            writeln!(self.writer, "<option>{}</option>", severity.as_str())?;
+        }
+        writeln!(self.writer, "</select></label>")?;
+        writeln!(self.writer, "</nav>")?;
+
+        writeln!(self.writer, r#"<table class="index">"#)?;
+        writeln!(
+            self.writer,
+            "<thead><tr><th>Lint</th><th>Matches</th></tr></thead>"
+        )?;
+        writeln!(self.writer, "<tbody>")?;
+        for (lint_name, count) in &self.lints {
+            let lint_name = escape(lint_name);
+            writeln!(
+                self.writer,
+                r##"<tr><td><a href="#" data-lint="{lint_name}">{lint_name}</a></td><td>{count}</td></tr>"##
+            )?;
+        }
+        writeln!(self.writer, "</tbody>")?;
+        writeln!(self.writer, "</table>")?;
+
+        for section in &self.sections {
+            write!(self.writer, "{section}")?;
+        }
+
+        writeln!(self.writer, "<script>{SCRIPT}</script>")?;
+        writeln!(self.writer, "</body>")?;
+        writeln!(self.writer, "</html>")?;
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+
+
+    /// Check that lines are rendered with syntax highlighting and
+    /// marked ranges.
+    #[test]
+    fn line_rendering() {
+        let code = b"return x < 1; // \xff";
+        let highlights = highlights(code);
+        let mut rendered = String::new();
+        let () = render_line(code, 0..code.len(), &highlights, &(7..12), &mut rendered);
+        assert_eq!(
+            rendered,
+            r#"<span class="keyword">return</span> <mark>x &lt; </mark><mark><span class="number">1</span></mark>; <span class="comment">// \xFF</span>"#
+        );
+    }
+
+    /// Check that we produce the expected HTML report.
+    #[test]
+    fn html_reporting() {
+        let code = indoc! { r#"
+          int main() {
+            bpf_probe_read(a, b, c);
+            return 0;
+          }
+        "# };
+        let m = LintMatch {
+            lint_name: "probe-read".to_string(),
+            message: "bpf_probe_read() is <deprecated>".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 15..29,
+                start_point: Point { row: 1, col: 2 },
+                end_point: Point { row: 1, col: 16 },
+            },
+            fix: None,
+        };
+        let opts = Opts {
+            extra_lines: Some((1, 0)),
+            ..Default::default()
+        };
+
+        let mut reporter = HtmlReporter::new(Vec::new(), opts);
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("a&b.bpf.c"), code.as_bytes());
+        let () = reporter.report_file(&file, &[m]).unwrap();
+        let file = SourceFile::new(Path::new("clean.bpf.c"), b"");
+        let () = reporter.report_file(&file, &[]).unwrap();
+        let () = reporter.end_run().unwrap();
+
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        assert!(report.starts_with("<!DOCTYPE html>\n"), "{report}");
+        assert!(report.ends_with("</html>\n"), "{report}");
+        // No external resources are referenced.
+        assert!(!report.contains("src="), "{report}");
+        assert!(!report.contains("href=\"http"), "{report}");
+
+        let expected = [
+            r#"<p class="summary">1 match(es) in 1 of 2 file(s)</p>"#,
+            r#"<option>probe-read</option>"#,
+            r##"<tr><td><a href="#" data-lint="probe-read">probe-read</a></td><td>1</td></tr>"##,
+            r#"<section class="file" id="file-0">"#,
+            r#"<h2>a&amp;b.bpf.c</h2>"#,
+            r#"<div class="match" data-lint="probe-read" data-severity="warning">"#,
+            r#"<strong>[probe-read]</strong> bpf_probe_read() is &lt;deprecated&gt;</p>"#,
+            r#"<p class="location">a&amp;b.bpf.c:2:3</p>"#,
+            r#"<span class="row">1</span><span class="type">int</span> <span class="function">main</span>() {"#,
+            r#"<span class="row">2</span>  <mark><span class="function">bpf_probe_read</span></mark>(a, b, c);"#,
+        ];
+        for expected in expected {
+            assert!(report.contains(expected), "{expected}\n{report}");
+        }
+        assert!(!report.contains("clean.bpf.c"), "{report}");
+        assert!(
+            !report.contains(r#"<span class="row">3</span>"#),
+            "{report}"
+        );
+    }
+}
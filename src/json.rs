@@ -0,0 +1,227 @@
+//! Support for emitting JSON.
+//!
+//! JSON output (of the Code Climate and SARIF reporters as well as of
+//! the WASM API) is written by hand instead of via `serde`. The
+//! `serde` derives are only available with the optional feature of
+//! the same name and producing JSON from them would additionally
+//! require `serde_json`, a dependency the library does not need
+//! otherwise. The documents in question are small and of fixed shape,
+//! and all string values are passed through [`escape`], which is the
+//! only JSON escaping logic in the crate.
+
+use std::borrow::Cow;
+use std::fmt::Write as _;
+
+
+/// Escape `text` for inclusion in a JSON string literal.
+pub(crate) fn escape(text: &str) -> Cow<'_, str> {
+    fn needs_escape(c: char) -> bool {
+        matches!(c, '"' | '\\') || c < '\u{20}'
+    }
+
+    if !text.contains(needs_escape) {
+        return Cow::Borrowed(text)
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < '\u{20}' => {
+                // SANITY: Writing to a `String` never fails.
+                let () = write!(escaped, "\\u{:04x}", u32::from(c)).unwrap();
+            },
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Formatting of lint results and lint meta data as JSON, as provided
+/// by the WASM API.
+///
+/// Like the JSON reporters, this module writes JSON by hand, without
+/// requiring the `serde` feature (see the parent module).
+#[cfg(any(test, target_arch = "wasm32"))]
+pub(crate) mod wasm {
+    use std::ops;
+
+    use crate::LineIndex;
+    use crate::LintMeta;
+    use crate::LintResults;
+    use crate::Point;
+    use crate::report::Opts;
+    use crate::report::snippet_rows;
+
+    use super::escape;
+
+
+    /// Format `range` as a JSON object.
+    fn range(range: &ops::Range<usize>) -> String {
+        format!(r#"{{"start":{},"end":{}}}"#, range.start, range.end)
+    }
+
+    /// Format `point` as a JSON object.
+    fn point(point: &Point) -> String {
+        format!(r#"{{"row":{},"col":{}}}"#, point.row, point.col)
+    }
+
+    /// Format the lint `results` for `code` as a JSON object.
+    ///
+    /// Each match is accompanied by the (zero-based, end-exclusive) rows
+    /// of its code snippet, including context lines as configured in
+    /// `opts`.
+    pub(crate) fn results(code: &[u8], results: &LintResults, opts: &Opts) -> String {
+        let index = LineIndex::new(code);
+        let matches = results
+            .matches
+            .iter()
+            .map(|r#match| {
+                let fix = r#match
+                    .fix
+                    .as_deref()
+                    .map(|fix| format!(r#""{}""#, escape(fix)))
+                    .unwrap_or_else(|| "null".to_string());
+                format!(
+                    r#"{{"lint_name":"{}","message":"{}","severity":"{}","range":{{"bytes":{},"start_point":{},"end_point":{}}},"snippet_rows":{},"fix":{fix}}}"#,
+                    escape(&r#match.lint_name),
+                    escape(&r#match.message),
+                    r#match.severity,
+                    range(&r#match.range.bytes),
+                    point(&r#match.range.start_point),
+                    point(&r#match.range.end_point),
+                    range(&snippet_rows(r#match, &index, opts)),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"{{"matches":[{matches}],"suppressed":{}}}"#,
+            results.suppressed
+        )
+    }
+
+    /// Format meta data about `lints` as a JSON array.
+    ///
+    /// Lints contained in `disabled` are reported as such.
+    pub(crate) fn lints(lints: &[LintMeta], disabled: &[String]) -> String {
+        let lints = lints
+            .iter()
+            .map(|lint| {
+                format!(
+                    r#"{{"name":"{}","severity":"{}","category":"{}","enabled":{},"autofix":{},"description":"{}","rationale":"{}","example_bad":"{}","example_good":"{}","url":"{}"}}"#,
+                    escape(&lint.name),
+                    lint.severity,
+                    escape(&lint.category),
+                    !disabled.contains(&lint.name),
+                    lint.autofix,
+                    escape(&lint.description),
+                    escape(&lint.rationale),
+                    escape(&lint.example_bad),
+                    escape(&lint.example_good),
+                    escape(&lint.url),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("[{lints}]")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::LintMeta;
+    use crate::LintOpts;
+    use crate::LintResults;
+    use crate::Severity;
+    use crate::lint_opts;
+    use crate::report::Opts;
+
+
+    /// Check that text is escaped as expected.
+    #[test]
+    fn escaping() {
+        assert!(matches!(escape("foo bar"), Cow::Borrowed("foo bar")));
+        assert_eq!(escape(r#"a "b" \c"#), r#"a \"b\" \\c"#);
+        assert_eq!(escape("a\tb\nc\r\u{1}"), r#"a\tb\nc\r\u0001"#);
+    }
+
+    /// Check that lint results are formatted as expected.
+    #[test]
+    fn results_formatting() {
+        let code = indoc! { r#"
+          int main() {
+            bpf_probe_read(a, b, c);
+            return 0
+          }
+        "# };
+        let opts = LintOpts {
+            syntax_errors: true,
+            ..Default::default()
+        };
+        let results = lint_opts(code.as_bytes(), &opts).unwrap();
+        let opts = Opts {
+            extra_lines: Some((1, 0)),
+            ..Default::default()
+        };
+        let json = wasm::results(code.as_bytes(), &results, &opts);
+        let expected = concat!(
+            r#"{"matches":["#,
+            r#"{"lint_name":"probe-read","message":"bpf_probe_read() is deprecated and replaced by bpf_probe_user() and bpf_probe_kernel(); refer to bpf-helpers(7)","severity":"warning","#,
+            r#""range":{"bytes":{"start":15,"end":29},"start_point":{"row":1,"col":2},"end_point":{"row":1,"col":16}},"#,
+            r#""snippet_rows":{"start":0,"end":2},"fix":"bpf_probe_read_kernel"},"#,
+            r#"{"lint_name":"syntax-error","message":"missing `;`","severity":"error","#,
+            r#""range":{"bytes":{"start":50,"end":50},"start_point":{"row":2,"col":10},"end_point":{"row":2,"col":10}},"#,
+            r#""snippet_rows":{"start":1,"end":3},"fix":null}"#,
+            r#"],"suppressed":0}"#,
+        );
+        assert_eq!(json, expected);
+
+        let json = wasm::results(b"", &LintResults::default(), &opts);
+        assert_eq!(json, r#"{"matches":[],"suppressed":0}"#);
+    }
+
+    /// Check that lint meta data is formatted as expected.
+    #[test]
+    fn lints_formatting() {
+        let lint = |name: &str, autofix| LintMeta {
+            name: name.to_string(),
+            source: String::new(),
+            severity: Severity::Error,
+            category: "general".to_string(),
+            autofix,
+            description: "Flags \"foo\".".to_string(),
+            rationale: "Because.\nReally.".to_string(),
+            example_bad: "foo();".to_string(),
+            example_good: "bar();".to_string(),
+            url: format!("https://example.com/{name}"),
+            _non_exhaustive: (),
+        };
+
+        assert_eq!(wasm::lints(&[], &[]), "[]");
+        let json = wasm::lints(
+            &[lint("foo", true), lint("bar", false)],
+            &["bar".to_string()],
+        );
+        let expected = concat!(
+            r#"[{"name":"foo","severity":"error","category":"general","enabled":true,"autofix":true,"#,
+            r#""description":"Flags \"foo\".","rationale":"Because.\nReally.","example_bad":"foo();","#,
+            r#""example_good":"bar();","url":"https://example.com/foo"},"#,
+            r#"{"name":"bar","severity":"error","category":"general","enabled":false,"autofix":false,"#,
+            r#""description":"Flags \"foo\".","rationale":"Because.\nReally.","example_bad":"foo();","#,
+            r#""example_good":"bar();","url":"https://example.com/bar"}]"#,
+        );
+        assert_eq!(json, expected);
+    }
+}
@@ -0,0 +1,232 @@
+use std::io;
+
+use anyhow::Result;
+
+use crate::LintMatch;
+use crate::Severity;
+use crate::reporter::Reporter;
+use crate::reporter::SourceFile;
+use crate::xml::escape;
+
+
+/// The name of the test suite containing all test cases.
+const SUITE_NAME: &str = "bpflint";
+
+
+/// A test case, representing a single linted file.
+#[derive(Debug)]
+struct TestCase {
+    /// The name of the test case, i.e., the path to the file.
+    name: String,
+    /// The rendered `<failure>` and `<error>` elements.
+    entries: Vec<String>,
+    /// Whether any of the file's matches has error severity.
+    has_errors: bool,
+}
+
+
+/// A [`Reporter`] producing XML test reports in the format popularized
+/// by [JUnit](https://junit.org).
+///
+/// Each linted file is represented by a `<testcase>` element. A file
+/// without matches passes, while each match in a file is reported as a
+/// `<failure>` element or, for matches with [`Severity::Error`], an
+/// `<error>` element. Locations are reported as 1-based lines and
+/// columns.
+///
+/// Because the report's root elements contain aggregate counts, the
+/// report is only written once the run has ended.
+///
+/// # Example
+/// ```xml
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <testsuites name="bpflint" tests="1" failures="1" errors="0">
+///   <testsuite name="bpflint" tests="1" failures="1" errors="0">
+///     <testcase name="example.bpf.c" classname="bpflint">
+///       <failure type="probe-read" message="bpf_probe_read() is deprecated">example.bpf.c:43:5: warning: [probe-read] bpf_probe_read() is deprecated</failure>
+///     </testcase>
+///   </testsuite>
+/// </testsuites>
+/// ```
+#[derive(Debug)]
+pub struct JUnitReporter<W> {
+    /// The writer to which to write the report.
+    writer: W,
+    /// The test cases of the run so far.
+    cases: Vec<TestCase>,
+}
+
+impl<W> JUnitReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`JUnitReporter`] writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            cases: Vec::new(),
+        }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Reporter for JUnitReporter<W>
+where
+    W: io::Write,
+{
+    fn begin_run(&mut self) -> Result<()> {
+        let () = self.cases.clear();
+        Ok(())
+    }
+
+    fn begin_file(&mut self, file: &SourceFile<'_>) -> Result<()> {
+        let () = self.cases.push(TestCase {
+            name: file.path().display().to_string(),
+            entries: Vec::new(),
+            has_errors: false,
+        });
+        Ok(())
+    }
+
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let LintMatch {
+            lint_name,
+            message,
+            severity,
+            range,
+            fix: _,
+        } = r#match;
+
+        let element = match severity {
+            Severity::Error => "error",
+            Severity::Note | Severity::Warning => "failure",
+        };
+        let text = format!(
+            "{}:{}:{}: {}: [{lint_name}] {message}",
+            file.path().display(),
+            range.start_point.row + 1,
+            range.start_point.col + 1,
+            severity.as_str(),
+        );
+        let entry = format!(
+            r#"<{element} type="{}" message="{}">{}</{element}>"#,
+            escape(lint_name),
+            escape(message),
+            escape(&text),
+        );
+
+        // SANITY: The file's test case got created in `begin_file`.
+        let case = self.cases.last_mut().unwrap();
+        let () = case.entries.push(entry);
+        case.has_errors |= *severity == Severity::Error;
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        let tests = self.cases.len();
+        let errors = self.cases.iter().filter(|case| case.has_errors).count();
+        let failures = self
+            .cases
+            .iter()
+            .filter(|case| !case.has_errors && !case.entries.is_empty())
+            .count();
+        let counts = format!(r#"tests="{tests}" failures="{failures}" errors="{errors}""#);
+
+        writeln!(self.writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(self.writer, r#"<testsuites name="{SUITE_NAME}" {counts}>"#)?;
+        writeln!(self.writer, r#"  <testsuite name="{SUITE_NAME}" {counts}>"#)?;
+        for case in &self.cases {
+            let name = escape(&case.name);
+            if case.entries.is_empty() {
+                writeln!(
+                    self.writer,
+                    r#"    <testcase name="{name}" classname="{SUITE_NAME}"/>"#
+                )?;
+            } else {
+                writeln!(
+                    self.writer,
+                    r#"    <testcase name="{name}" classname="{SUITE_NAME}">"#
+                )?;
+                for entry in &case.entries {
+                    writeln!(self.writer, "      {entry}")?;
+                }
+                writeln!(self.writer, "    </testcase>")?;
+            }
+        }
+        writeln!(self.writer, "  </testsuite>")?;
+        writeln!(self.writer, "</testsuites>")?;
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+
+
+    /// Check that we produce the expected XML test report.
+    #[test]
+    fn junit_reporting() {
+        let m = |lint_name: &str, severity| LintMatch {
+            lint_name: lint_name.to_string(),
+            message: format!("{lint_name} is \"bad\""),
+            severity,
+            range: Range {
+                bytes: 160..174,
+                start_point: Point { row: 6, col: 4 },
+                end_point: Point { row: 6, col: 18 },
+            },
+            fix: None,
+        };
+
+        let mut reporter = JUnitReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("a.bpf.c"), b"");
+        let () = reporter
+            .report_file(&file, &[m("foo", Severity::Warning)])
+            .unwrap();
+        let file = SourceFile::new(Path::new("b.bpf.c"), b"");
+        let () = reporter
+            .report_file(
+                &file,
+                &[m("foo", Severity::Note), m("bar", Severity::Error)],
+            )
+            .unwrap();
+        let file = SourceFile::new(Path::new("c.bpf.c"), b"");
+        let () = reporter.report_file(&file, &[]).unwrap();
+        let () = reporter.end_run().unwrap();
+
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        let expected = indoc! { r#"
+          <?xml version="1.0" encoding="UTF-8"?>
+          <testsuites name="bpflint" tests="3" failures="1" errors="1">
+            <testsuite name="bpflint" tests="3" failures="1" errors="1">
+              <testcase name="a.bpf.c" classname="bpflint">
+                <failure type="foo" message="foo is &quot;bad&quot;">a.bpf.c:7:5: warning: [foo] foo is &quot;bad&quot;</failure>
+              </testcase>
+              <testcase name="b.bpf.c" classname="bpflint">
+                <failure type="foo" message="foo is &quot;bad&quot;">b.bpf.c:7:5: note: [foo] foo is &quot;bad&quot;</failure>
+                <error type="bar" message="bar is &quot;bad&quot;">b.bpf.c:7:5: error: [bar] bar is &quot;bad&quot;</error>
+              </testcase>
+              <testcase name="c.bpf.c" classname="bpflint"/>
+            </testsuite>
+          </testsuites>
+        "# };
+        assert_eq!(report, expected);
+    }
+}
@@ -21,32 +21,72 @@
 //! In the above examples, none of the instances of `bpf_probe_read`
 //! will be flagged.
 //!
+//! Multiple lints can be disabled at once by separating their names
+//! with commas, e.g., `bpflint: disable=probe-read,untyped-map-member`.
 //! The directive `bpflint: disable=all` acts as a catch-all, disabling
-//! reporting of all lints.
+//! reporting of all lints. [`suppression_edit`] can be used to create
+//! such a comment for a given lint match.
+//!
+//! To disable lints for an entire file, use a top-level comment with a
+//! `disable-file` directive, which accepts the same lists of names:
+//! ```c
+//! /* bpflint: disable-file=probe-read,unstable-attach-point */
+//! ```
+//!
+//! Each lint match carries a [`Severity`]. Lints may specify it via a
+//! `severity` property (`note`, `warning`, or `error`) and default to
+//! [`Severity::Warning`] otherwise.
+//!
+//! Source code is expected to be encoded as UTF-8 and may use `\n` as
+//! well as `\r\n` line endings. Columns, as reported in [`Point`]
+//! objects, are byte offsets into the respective line. Code that is
+//! not valid UTF-8 is still linted. When reporting matches in such
+//! code, each byte not forming valid UTF-8 is rendered as a `\xNN`
+//! escape sequence, with carets aligned accordingly.
+
+#![cfg_attr(all(test, feature = "nightly"), feature(test))]
+
+#[cfg(all(test, feature = "nightly"))]
+extern crate test;
 
 #[cfg(target_arch = "wasm32")]
 #[macro_use]
 mod redefine;
 
+mod checkstyle;
+mod codeclimate;
+mod doc;
+mod document;
+mod edit;
+mod github;
+mod html;
+mod json;
+mod junit;
 mod lines;
 mod lint;
+mod markdown;
 mod report;
+mod reporter;
+mod sarif;
+mod xml;
 
 use std::ops;
 
 
 /// A position in a multi-line text document, in terms of rows and columns.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Point {
     /// A row number in source code (zero-based).
     pub row: usize,
-    /// A column number in source code (zero-based).
+    /// A column number in source code (zero-based), in bytes.
     pub col: usize,
 }
 
 /// A range of positions in a multi-line text document, both in terms of bytes
 /// and of rows and columns.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Range {
     /// The byte range in the source code.
     pub bytes: ops::Range<usize>,
@@ -56,13 +96,42 @@ pub struct Range {
     pub end_point: Point,
 }
 
+pub use crate::checkstyle::CheckstyleReporter;
+pub use crate::codeclimate::CodeClimateReporter;
+pub use crate::document::Document;
+pub use crate::edit::SuppressionScope;
+pub use crate::edit::TextEdit;
+pub use crate::edit::apply_edits;
+pub use crate::edit::suppression_edit;
+pub use crate::github::GitHubReporter;
+pub use crate::html::HtmlReporter;
+pub use crate::junit::JUnitReporter;
+pub use crate::lines::LineIndex;
+pub use crate::lint::INCOMPLETE_RESULTS;
 pub use crate::lint::LintMatch;
 pub use crate::lint::LintMeta;
+pub use crate::lint::LintOpts;
+pub use crate::lint::LintResults;
+pub use crate::lint::LintTimings;
+pub use crate::lint::SYNTAX_ERROR;
+pub use crate::lint::Severity;
 pub use crate::lint::builtin_lints;
 pub use crate::lint::lint;
+pub use crate::lint::lint_opts;
+pub use crate::lint::parse;
+pub use crate::markdown::MarkdownReporter;
 pub use crate::report::Opts;
 pub use crate::report::report_terminal;
+pub use crate::report::report_terminal_matches;
 pub use crate::report::report_terminal_opts;
+pub use crate::report::report_terminal_with_index;
+pub use crate::reporter::Reporter;
+pub use crate::reporter::SourceFile;
+pub use crate::reporter::TerminalReporter;
+pub use crate::sarif::SarifReporter;
+
+/// The syntax tree of BPF C source code, as created by [`parse`].
+pub use tree_sitter::Tree;
 
 #[cfg(target_arch = "wasm32")]
 mod wasm {
@@ -70,31 +139,175 @@ mod wasm {
 
     use anyhow::Context as _;
     use anyhow::Error;
+    use anyhow::anyhow;
 
     use wasm_bindgen::prelude::wasm_bindgen;
 
     use super::*;
 
+    use crate::json;
+
+
+    /// Configuration of the linting and reporting performed by the
+    /// functions in this module.
+    #[wasm_bindgen]
+    #[derive(Clone, Debug, Default)]
+    pub struct Config {
+        /// The names of the lints to run, or `None` for all of them.
+        enabled: Option<Vec<String>>,
+        /// The number of context lines to report before and after each
+        /// match.
+        context: (u8, u8),
+        /// Whether to report syntax errors.
+        syntax_errors: bool,
+    }
+
+    #[wasm_bindgen]
+    impl Config {
+        /// Create a new configuration, running all lints and reporting
+        /// neither context lines nor syntax errors.
+        #[wasm_bindgen(constructor)]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Set the names of the lints to run.
+        pub fn set_enabled_lints(&mut self, lints: Vec<String>) {
+            self.enabled = Some(lints);
+        }
+
+        /// Set the number of context lines to report before and after
+        /// each match.
+        pub fn set_context_lines(&mut self, before: u8, after: u8) {
+            self.context = (before, after);
+        }
+
+        /// Set whether to report code that fails to parse as matches of
+        /// the `syntax-error` lint.
+        ///
+        /// The parser does not understand all BPF C constructs, such as
+        /// `SEC()` attributes on declarations, so valid code may be
+        /// reported as well.
+        pub fn set_syntax_errors(&mut self, syntax_errors: bool) {
+            self.syntax_errors = syntax_errors;
+        }
+    }
+
+    impl Config {
+        /// Retrieve the names of the lints not to run.
+        fn disabled_lints(&self) -> Result<Vec<String>, Error> {
+            let Some(enabled) = &self.enabled else {
+                return Ok(Vec::new())
+            };
+
+            let lints = builtin_lints().map(|lint| lint.name).collect::<Vec<_>>();
+            if let Some(name) = enabled.iter().find(|name| !lints.contains(name)) {
+                return Err(anyhow!("unknown lint `{name}`"))
+            }
+            let disabled = lints
+                .into_iter()
+                .filter(|name| !enabled.contains(name))
+                .collect();
+            Ok(disabled)
+        }
+
+        /// Create the options for linting code.
+        fn lint_opts(&self) -> Result<LintOpts, Error> {
+            let opts = LintOpts {
+                syntax_errors: self.syntax_errors,
+                disabled_lints: self.disabled_lints()?,
+                ..Default::default()
+            };
+            Ok(opts)
+        }
+
+        /// Create the options for reporting matches.
+        fn report_opts(&self) -> Opts {
+            Opts {
+                extra_lines: Some(self.context),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Produce an HTML rendered terminal report of `matches` in `code`
+    /// representing a file at `path`.
+    fn report_html(
+        code: &[u8],
+        path: PathBuf,
+        matches: &[LintMatch],
+        opts: Opts,
+    ) -> Result<String, Error> {
+        let file = SourceFile::new(&path, code);
+        let mut reporter = TerminalReporter::new(Vec::new(), opts);
+        let () = reporter.begin_run()?;
+        let () = reporter.report_file(&file, matches)?;
+        let () = reporter.end_run()?;
+        let report = reporter.into_inner();
+        let report =
+            String::from_utf8(report).context("generated report contains invalid UTF-8")?;
+        Ok(report)
+    }
+
     /// Lint source code `code` representing a file at `path` and
     /// produce a report, end-to-end.
     ///
-    /// This function exists mostly because exposing something like our
-    /// `LintMatch` type across WASM's ABI is a major PITA and our
-    /// interactive service only cares about the end-to-end workflow
-    /// anyway.
+    /// Use [`lint_html_config`] for control over the lints run and the
+    /// context reported, or [`lint_json`] for structured results.
     #[wasm_bindgen]
     pub fn lint_html(code: Vec<u8>, path: String) -> Result<String, String> {
         fn lint_impl(code: Vec<u8>, path: PathBuf) -> Result<String, Error> {
-            let mut report = Vec::new();
             let matches = lint(&code)?;
-            for m in matches {
-                let () = report_terminal(&m, &code, &path, &mut report)?;
-            }
-            let report =
-                String::from_utf8(report).context("generated report contains invalid UTF-8")?;
-            Ok(report)
+            report_html(&code, path, &matches, Opts::default())
         }
 
         lint_impl(code, PathBuf::from(path)).map_err(|err| format!("{err:?}"))
     }
+
+    /// Lint source code `code` representing a file at `path` as
+    /// configured by `config` and produce a report, end-to-end.
+    #[wasm_bindgen]
+    pub fn lint_html_config(
+        code: Vec<u8>,
+        path: String,
+        config: &Config,
+    ) -> Result<String, String> {
+        fn lint_impl(code: Vec<u8>, path: PathBuf, config: &Config) -> Result<String, Error> {
+            let results = lint_opts(&code, &config.lint_opts()?)?;
+            report_html(&code, path, &results.matches, config.report_opts())
+        }
+
+        lint_impl(code, PathBuf::from(path), config).map_err(|err| format!("{err:?}"))
+    }
+
+    /// Lint source code `code` as configured by `config` and report the
+    /// results as JSON.
+    ///
+    /// The result is an object containing a `matches` array and the
+    /// number of `suppressed` matches. Each match provides the lint
+    /// name, message, severity, and range (in terms of bytes as well as
+    /// start and end points with byte based columns), the rows of its
+    /// code snippet including context lines, and the suggested fix, if
+    /// any. Syntax errors, if enabled, are reported as matches of the
+    /// `syntax-error` lint.
+    #[wasm_bindgen]
+    pub fn lint_json(code: Vec<u8>, config: &Config) -> Result<String, String> {
+        fn lint_impl(code: Vec<u8>, config: &Config) -> Result<String, Error> {
+            let results = lint_opts(&code, &config.lint_opts()?)?;
+            Ok(json::wasm::results(&code, &results, &config.report_opts()))
+        }
+
+        lint_impl(code, config).map_err(|err| format!("{err:?}"))
+    }
+
+    /// List the built-in lints along with their meta data, as a JSON
+    /// array.
+    ///
+    /// Lints not enabled in `config` are reported as such.
+    #[wasm_bindgen]
+    pub fn lints_json(config: &Config) -> Result<String, String> {
+        let disabled = config.disabled_lints().map_err(|err| format!("{err:?}"))?;
+        let lints = builtin_lints().collect::<Vec<_>>();
+        Ok(json::wasm::lints(&lints, &disabled))
+    }
 }
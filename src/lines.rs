@@ -3,41 +3,44 @@ use std::iter::FusedIterator;
 
 /// An iterator over the lines in a byte slice (typically representing
 /// code).
+///
+/// Lines are terminated by `\n` or `\r\n`, neither of which is part of
+/// the reported lines. A trailing line terminator starts another
+/// (empty) line. A carriage return not followed by `\n` (including one
+/// at the very end of the code) does not terminate a line and is
+/// reported as part of it, mirroring how the parser counts columns.
 #[derive(Debug)]
 pub(crate) struct Lines<'src> {
     /// The source code in question.
     code: &'src [u8],
-    /// The index at which we continue forward searching for lines.
+    /// The index of the start of the next line to report.
     idx: Option<usize>,
 }
 
 impl<'src> Lines<'src> {
     /// Create a new [`Lines`] object, referencing the snippet `code` and
-    /// starting line discovery/iteration at index `idx`.
-    pub fn new(code: &'src [u8], idx: usize) -> Self {
+    /// starting iteration at index `start`, which has to be the start
+    /// of a line.
+    ///
+    /// Use [`LineIndex::lines`] to find the lines starting at a given
+    /// row.
+    fn new(code: &'src [u8], start: usize) -> Self {
         debug_assert!(
-            idx <= code.len(),
-            "invalid index `{idx}` for slice with length `{}`",
+            start <= code.len(),
+            "invalid index `{start}` for slice with length `{}`",
             code.len()
         );
+        debug_assert!(
+            start == 0 || code[start - 1] == b'\n',
+            "index `{start}` does not refer to the start of a line"
+        );
 
         Self {
             code,
-            idx: Some(idx),
+            idx: Some(start),
         }
     }
 
-    #[track_caller]
-    fn find_line_start(code: &[u8], idx: usize) -> usize {
-        // SANITY: The caller has to ensure that `idx` always maps to a
-        //         valid position.
-        code[..idx]
-            .iter()
-            .rposition(|&b| b == b'\n')
-            .map(|idx| idx + 1)
-            .unwrap_or(0)
-    }
-
     #[track_caller]
     fn find_line_end(code: &[u8], idx: usize) -> usize {
         // SANITY: The caller has to ensure that `idx` always maps to a
@@ -51,17 +54,19 @@ impl<'src> Iterator for Lines<'src> {
     type Item = &'src [u8];
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(idx) = self.idx {
-            let start = Self::find_line_start(self.code, idx);
-            let end = Self::find_line_end(self.code, idx);
-            let line = &self.code[start..end];
-
-            let next_idx = end + 1;
-            if next_idx >= self.code.len() {
-                self.idx = None;
-            } else {
-                self.idx = Some(next_idx);
+        // NB: `idx` always refers to the start of a line.
+        if let Some(start) = self.idx {
+            let end = Self::find_line_end(self.code, start);
+            let mut line = &self.code[start..end];
+            // Treat CRLF sequences as line breaks as well, without
+            // exposing the carriage return as part of the line.
+            if end < self.code.len() {
+                line = line.strip_suffix(b"\r").unwrap_or(line);
             }
+
+            // NB: A trailing newline starts another (empty) line, just
+            //     as it does for `LineIndex`.
+            self.idx = (end < self.code.len()).then_some(end + 1);
             Some(line)
         } else {
             None
@@ -72,38 +77,84 @@ impl<'src> Iterator for Lines<'src> {
 impl FusedIterator for Lines<'_> {}
 
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// An index of the lines in a snippet of code, allowing for constant
+/// time lookup of a line by its (zero-based) row.
+///
+/// Building the index requires a single pass over the code. It is
+/// meant to be created once per file and then shared for reporting
+/// all matches in said file.
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    /// The byte offsets at which the individual lines start.
+    starts: Vec<usize>,
+}
 
-    use indoc::indoc;
+impl LineIndex {
+    /// Create a new [`LineIndex`] for the provided `code`.
+    pub fn new(code: &[u8]) -> Self {
+        let starts = [0]
+            .into_iter()
+            .chain(
+                code.iter()
+                    .enumerate()
+                    .filter(|(_idx, b)| **b == b'\n')
+                    .map(|(idx, _b)| idx + 1),
+            )
+            .collect();
+
+        Self { starts }
+    }
 
+    /// Retrieve the number of lines in the indexed code.
+    ///
+    /// A trailing newline starts another (empty) line.
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
 
-    /// Check that the [`Lines::find_line_start`] function works as it
-    /// should.
-    #[test]
-    fn line_start_finding() {
-        assert_eq!(Lines::find_line_start(b"", 0), 0);
-        assert_eq!(Lines::find_line_start(b"a", 0), 0);
-        assert_eq!(Lines::find_line_start(b"a", 1), 0);
+    /// Retrieve the byte offset at which the line at `row` starts, if
+    /// any.
+    #[inline]
+    pub fn line_start(&self, row: usize) -> Option<usize> {
+        self.starts.get(row).copied()
+    }
 
-        let code = b"\n";
-        assert_eq!(Lines::find_line_start(code, 0), 0);
-        assert_eq!(Lines::find_line_start(code, 1), 1);
+    /// Retrieve the (zero-based) row of the line containing the byte at
+    /// `offset`.
+    ///
+    /// Offsets past the end of the indexed code map to the last line.
+    #[inline]
+    pub(crate) fn row(&self, offset: usize) -> usize {
+        // SANITY: The first line always starts at offset zero, so the
+        //         partition point is always at least one.
+        self.starts.partition_point(|start| *start <= offset) - 1
+    }
 
-        let code = b"a\n";
-        assert_eq!(Lines::find_line_start(code, 0), 0);
-        assert_eq!(Lines::find_line_start(code, 1), 0);
-        assert_eq!(Lines::find_line_start(code, 2), 2);
+    /// Retrieve an iterator over the lines of `code`, starting with the
+    /// one at `row`, if any.
+    ///
+    /// `code` has to be the code the index was created for.
+    pub(crate) fn lines<'src>(&self, code: &'src [u8], row: usize) -> Option<Lines<'src>> {
+        let start = self.line_start(row)?;
+        Some(Lines::new(code, start))
+    }
 
-        let code = b"ab\ncd";
-        assert_eq!(Lines::find_line_start(code, 0), 0);
-        assert_eq!(Lines::find_line_start(code, 1), 0);
-        assert_eq!(Lines::find_line_start(code, 2), 0);
-        assert_eq!(Lines::find_line_start(code, 3), 3);
-        assert_eq!(Lines::find_line_start(code, 4), 3);
-        assert_eq!(Lines::find_line_start(code, 5), 3);
+    /// Retrieve the line of `code` at `row`, if any.
+    ///
+    /// `code` has to be the code the index was created for.
+    pub(crate) fn line<'src>(&self, code: &'src [u8], row: usize) -> Option<&'src [u8]> {
+        self.lines(code, row)?.next()
     }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
 
     /// Check that the [`Lines::find_line_end`] function works as it
     /// should.
@@ -147,8 +198,13 @@ mod tests {
         assert_eq!(lines.next(), Some(b"a".as_slice()));
         assert_eq!(lines.next(), None);
 
-        let mut lines = Lines::new(b"a", 1);
+        let mut lines = Lines::new(b"a\n", 0);
         assert_eq!(lines.next(), Some(b"a".as_slice()));
+        assert_eq!(lines.next(), Some(b"".as_slice()));
+        assert_eq!(lines.next(), None);
+
+        let mut lines = Lines::new(b"a\n", 2);
+        assert_eq!(lines.next(), Some(b"".as_slice()));
         assert_eq!(lines.next(), None);
 
         let code = indoc! { br#"
@@ -161,18 +217,87 @@ mod tests {
         assert_eq!(lines.next(), Some(b"abc".as_slice()));
         assert_eq!(lines.next(), Some(b"cde".as_slice()));
         assert_eq!(lines.next(), Some(b"fgh".as_slice()));
+        assert_eq!(lines.next(), Some(b"".as_slice()));
         assert_eq!(lines.next(), None);
 
-        let mut lines = Lines::new(code, 3);
-        assert_eq!(lines.next(), Some(b"abc".as_slice()));
+        let mut lines = Lines::new(code, 4);
         assert_eq!(lines.next(), Some(b"cde".as_slice()));
         assert_eq!(lines.next(), Some(b"fgh".as_slice()));
+        assert_eq!(lines.next(), Some(b"".as_slice()));
         assert_eq!(lines.next(), None);
+    }
 
-        let mut lines = Lines::new(code, 4);
+    /// Check that CRLF line endings are not reported as part of lines,
+    /// while lone carriage returns are.
+    #[test]
+    fn crlf_iteration() {
+        let code = b"abc\r\ncde\r\n\r\nfgh\r";
+        let mut lines = Lines::new(code, 0);
+        assert_eq!(lines.next(), Some(b"abc".as_slice()));
         assert_eq!(lines.next(), Some(b"cde".as_slice()));
+        assert_eq!(lines.next(), Some(b"".as_slice()));
+        assert_eq!(lines.next(), Some(b"fgh\r".as_slice()));
+        assert_eq!(lines.next(), None);
+
+        let mut lines = Lines::new(code, 5);
+        assert_eq!(lines.next(), Some(b"cde".as_slice()));
+
+        let mut lines = Lines::new(b"a\rb\r\n", 0);
+        assert_eq!(lines.next(), Some(b"a\rb".as_slice()));
+        assert_eq!(lines.next(), Some(b"".as_slice()));
+        assert_eq!(lines.next(), None);
+    }
+
+    /// Check that we can look up lines via a [`LineIndex`].
+    #[test]
+    fn line_index_lookup() {
+        let index = LineIndex::new(b"");
+        assert_eq!(index.line_count(), 1);
+        assert_eq!(index.line_start(0), Some(0));
+        assert_eq!(index.line_start(1), None);
+
+        let code = indoc! { br#"
+          abc
+
+          fgh
+        "# };
+        let index = LineIndex::new(code);
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_start(0), Some(0));
+        assert_eq!(index.line_start(1), Some(4));
+        assert_eq!(index.line_start(2), Some(5));
+        assert_eq!(index.line_start(3), Some(9));
+        assert_eq!(index.line_start(4), None);
+        assert_eq!(index.row(0), 0);
+        assert_eq!(index.row(3), 0);
+        assert_eq!(index.row(4), 1);
+        assert_eq!(index.row(5), 2);
+        assert_eq!(index.row(8), 2);
+        assert_eq!(index.row(9), 3);
+        assert_eq!(index.row(42), 3);
+
+        let mut lines = index.lines(code, 1).unwrap();
+        assert_eq!(lines.next(), Some(b"".as_slice()));
         assert_eq!(lines.next(), Some(b"fgh".as_slice()));
+        assert_eq!(lines.next(), Some(b"".as_slice()));
         assert_eq!(lines.next(), None);
+        // The iterator yields exactly as many lines as the index knows
+        // about.
+        assert_eq!(index.lines(code, 0).unwrap().count(), index.line_count());
+
+        let mut lines = index.lines(code, 3).unwrap();
+        assert_eq!(lines.next(), Some(b"".as_slice()));
+        assert_eq!(lines.next(), None);
+        assert!(index.lines(code, 4).is_none());
+
+        assert_eq!(index.line(code, 0), Some(b"abc".as_slice()));
+        assert_eq!(index.line(code, 2), Some(b"fgh".as_slice()));
+        assert_eq!(index.line(code, 4), None);
+
+        let code = b"abc\r\ncde";
+        let index = LineIndex::new(code);
+        assert_eq!(index.line(code, 0), Some(b"abc".as_slice()));
+        assert_eq!(index.line(code, 1), Some(b"cde".as_slice()));
     }
 
     /// Make sure that we fail [`Lines`] construction with an invalid
@@ -192,4 +317,13 @@ mod tests {
     fn invalid_initial_index_2() {
         let _lines = Lines::new(b"a", 2);
     }
+
+    /// Make sure that we fail [`Lines`] construction with an index not
+    /// referring to the start of a line.
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "index `3` does not refer to the start of a line"]
+    fn invalid_initial_index_3() {
+        let _lines = Lines::new(b"abc\ncde", 3);
+    }
 }
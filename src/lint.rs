@@ -1,7 +1,18 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::iter;
+use std::ops;
 use std::str;
+use std::str::FromStr;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
 use anyhow::Context as _;
+use anyhow::Error;
 use anyhow::Result;
+use anyhow::anyhow;
 
 use tracing::warn;
 
@@ -9,15 +20,21 @@ use tree_sitter::Node;
 use tree_sitter::Parser;
 use tree_sitter::Query;
 use tree_sitter::QueryCursor;
+use tree_sitter::QueryCursorOptions;
+use tree_sitter::QueryCursorState;
+use tree_sitter::QueryMatch;
+use tree_sitter::QueryProperty;
 use tree_sitter::StreamingIterator as _;
 use tree_sitter::Tree;
 use tree_sitter_bpf_c::LANGUAGE;
 
 use crate::Point;
 use crate::Range;
+use crate::doc::LintDoc;
+use crate::edit::TextEdit;
 
 
-mod lints {
+pub(crate) mod lints {
     include!(concat!(env!("OUT_DIR"), "/lints.rs"));
 }
 
@@ -28,6 +45,13 @@ impl From<tree_sitter::Point> for Point {
     }
 }
 
+impl From<Point> for tree_sitter::Point {
+    fn from(other: Point) -> Self {
+        let Point { row, col } = other;
+        Self { row, column: col }
+    }
+}
+
 impl From<tree_sitter::Range> for Range {
     fn from(other: tree_sitter::Range) -> Self {
         let tree_sitter::Range {
@@ -45,71 +69,371 @@ impl From<tree_sitter::Range> for Range {
 }
 
 
+/// The severity of a lint match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// A remark that is purely informational.
+    Note,
+    /// A potential problem that should likely be addressed.
+    #[default]
+    Warning,
+    /// A definite problem that has to be addressed.
+    Error,
+}
+
+impl Severity {
+    /// Retrieve the textual representation of the severity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "note" => Ok(Self::Note),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            _ => Err(anyhow!(
+                "invalid severity `{s}` (expected one of `note`, `warning`, `error`)"
+            )),
+        }
+    }
+}
+
+
 /// Meta data about a lint.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LintMeta {
     /// The lint's name.
     pub name: String,
+    /// The lint's `tree-sitter` query source code.
+    pub source: String,
+    /// The severity with which the lint's matches are reported.
+    pub severity: Severity,
+    /// The category the lint belongs to, e.g., `deprecated`.
+    pub category: String,
+    /// Whether the lint's matches come with a suggested fix.
+    pub autofix: bool,
+    /// A description of what the lint flags.
+    pub description: String,
+    /// The reasoning behind the lint.
+    pub rationale: String,
+    /// An example of code that is flagged by the lint.
+    pub example_bad: String,
+    /// An example of code corresponding to `example_bad` that is not
+    /// flagged.
+    pub example_good: String,
+    /// A link to the lint's documentation.
+    pub url: String,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub _non_exhaustive: (),
 }
 
 
+/// The category of lints not setting the `category` property.
+const DEFAULT_CATEGORY: &str = "general";
+
+
+/// Meta data about a lint, as contained in its query.
+struct QueryMeta {
+    severity: Severity,
+    category: String,
+    autofix: bool,
+}
+
+impl QueryMeta {
+    /// Retrieve the meta data configured by the lint query `lint_src`.
+    fn new(lint_name: &str, lint_src: &str) -> Result<Self> {
+        let query = compile_query(lint_src)?;
+        let settings = query.property_settings(0);
+        let severity = find_property(settings, "severity", lint_name)?
+            .map(Severity::from_str)
+            .transpose()
+            .with_context(|| format!("{lint_name}: `severity` property is invalid"))?
+            .unwrap_or_default();
+        let category = find_property(settings, "category", lint_name)?
+            .unwrap_or(DEFAULT_CATEGORY)
+            .to_string();
+        let autofix = find_property(settings, "fix", lint_name)?.is_some();
+
+        let slf = Self {
+            severity,
+            category,
+            autofix,
+        };
+        Ok(slf)
+    }
+}
+
+
 /// Retrieve the list of lints shipped with the library.
 pub fn builtin_lints() -> impl ExactSizeIterator<Item = LintMeta> + DoubleEndedIterator {
-    lints::LINTS.iter().map(|(name, _code)| LintMeta {
-        name: name.to_string(),
-        _non_exhaustive: (),
-    })
+    lints::LINTS
+        .iter()
+        .zip(lints::DOCS.iter())
+        .map(|((name, code), doc)| {
+            // SANITY: Queries and documentation of built-in lints are
+            //         validated by tests.
+            let QueryMeta {
+                severity,
+                category,
+                autofix,
+            } = QueryMeta::new(name, code).unwrap();
+            let LintDoc {
+                description,
+                rationale,
+                example_bad,
+                example_good,
+            } = LintDoc::parse(name, doc).unwrap();
+
+            LintMeta {
+                name: name.to_string(),
+                source: code.to_string(),
+                severity,
+                category,
+                autofix,
+                description,
+                rationale,
+                example_bad,
+                example_good,
+                url: format!("https://github.com/d-e-s-o/bpflint/blob/main/lints/{name}.md"),
+                _non_exhaustive: (),
+            }
+        })
 }
 
 
 /// Details about a lint match.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LintMatch {
     /// The name of the lint that matched.
     pub lint_name: String,
     /// The lint's message.
     pub message: String,
+    /// The severity of the match.
+    pub severity: Severity,
     /// The code range that triggered the lint.
     pub range: Range,
+    /// A suggested replacement for the code in `range`, if the lint
+    /// provides one.
+    pub fix: Option<String>,
+}
+
+impl LintMatch {
+    /// Retrieve the edit applying the fix suggested for the match, if
+    /// any.
+    pub fn fix_edit(&self) -> Option<TextEdit> {
+        self.fix.as_ref().map(|fix| TextEdit {
+            range: self.range.clone(),
+            text: fix.clone(),
+        })
+    }
+}
+
+
+/// The results of linting a piece of code.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LintResults {
+    /// The lint matches found, in source code order.
+    pub matches: Vec<LintMatch>,
+    /// The number of matches that were suppressed by `bpflint:
+    /// disable=...` comments in the code.
+    pub suppressed: usize,
+    /// The names of lints whose queries exceeded the maximum number of
+    /// in-progress matches, meaning that their matches may be
+    /// incomplete.
+    pub exceeded_match_limit: Vec<String>,
+    /// Timing information, if requested via [`LintOpts::timings`].
+    pub timings: Option<LintTimings>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+
+/// Timing information about the linting of a piece of code.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LintTimings {
+    /// The time it took to parse the code.
+    pub parse: Duration,
+    /// The time it took to run each lint, in the order in which lints
+    /// were run.
+    pub lints: Vec<(String, Duration)>,
+}
+
+
+/// Measure the time it takes to evaluate `f`, if `enabled`.
+#[cfg(not(target_arch = "wasm32"))]
+fn measure<T>(enabled: bool, f: impl FnOnce() -> T) -> (T, Option<Duration>) {
+    if enabled {
+        let start = Instant::now();
+        let result = f();
+        (result, Some(start.elapsed()))
+    } else {
+        (f(), None)
+    }
 }
 
+/// Measure the time it takes to evaluate `f`, if `enabled`.
+///
+/// Time measurement is unsupported in WASM contexts, where no time is
+/// ever reported.
+#[cfg(target_arch = "wasm32")]
+fn measure<T>(_enabled: bool, f: impl FnOnce() -> T) -> (T, Option<Duration>) {
+    (f(), None)
+}
+
+
+/// The name of the pseudo-lint used for reporting syntax errors.
+pub const SYNTAX_ERROR: &str = "syntax-error";
+
+/// The name of the pseudo-lint used for reporting that the matches of
+/// a lint may be incomplete, because its query exceeded the match
+/// limit or the time budget was exhausted.
+pub const INCOMPLETE_RESULTS: &str = "incomplete-results";
+
+
+/// Options for linting code.
+#[derive(Default, Clone, Debug)]
+pub struct LintOpts {
+    /// Whether to report syntax errors in the provided code as matches
+    /// of the [`SYNTAX_ERROR`] pseudo-lint, with [`Severity::Error`].
+    ///
+    /// Note that `tree-sitter` recovers from syntax errors and lints
+    /// still run on the remainder of the code.
+    pub syntax_errors: bool,
+    /// The names of lints that should not be run.
+    pub disabled_lints: Vec<String>,
+    /// Whether to measure the time it takes to parse the code and to
+    /// run individual lints, reported in [`LintResults::timings`].
+    ///
+    /// Time measurement is not supported on `wasm32` targets, where
+    /// this option is ignored.
+    pub timings: bool,
+    /// The maximum number of in-progress matches per lint query.
+    ///
+    /// Queries exceeding this limit may drop matches, which is
+    /// reported as a match of the [`INCOMPLETE_RESULTS`] pseudo-lint.
+    /// The limit has to be in the range `1..=65536`. If not set, the
+    /// `tree-sitter` default is used.
+    pub match_limit: Option<u32>,
+    /// The time budget for linting the provided code, including
+    /// parsing it.
+    ///
+    /// Lints that do not finish in time are reported as matches of the
+    /// [`INCOMPLETE_RESULTS`] pseudo-lint. Time budgets are not
+    /// supported on `wasm32` targets, where this option is ignored.
+    pub timeout: Option<Duration>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+
+/// Retrieve the `bpflint:` directive contained in the code comment
+/// `comment`, if any.
+fn directive(comment: &str) -> Option<&str> {
+    // The comment node will still contain the actual comment syntax,
+    // unfortunately.
+    let comment = comment.trim_start_matches("//");
+    let comment = comment.trim_start_matches("/*");
+    let comment = comment.trim_end_matches("*/");
+    let comment = comment.trim();
+
+    let directive = comment.strip_prefix("bpflint:")?.trim();
+    Some(directive)
+}
+
+/// Retrieve the target of a `bpflint: disable=<target>` directive
+/// contained in the code comment `comment`, if any.
+pub(crate) fn disable_directive(comment: &str) -> Option<&str> {
+    directive(comment)?.strip_prefix("disable=")
+}
+
+/// Retrieve the target of a `bpflint: disable-file=<target>` directive
+/// contained in the code comment `comment`, if any.
+pub(crate) fn disable_file_directive(comment: &str) -> Option<&str> {
+    directive(comment)?.strip_prefix("disable-file=")
+}
+
+/// Iterate over the top-level comment nodes of `tree` containing
+/// `bpflint: disable-file=<target>` directives, along with the text of
+/// the comment and the directive's target.
+pub(crate) fn file_directives<'tree, 'code>(
+    tree: &'tree Tree,
+    code: &'code [u8],
+) -> impl Iterator<Item = (Node<'tree>, &'code str, &'code str)> {
+    let root = tree.root_node();
+    (0..root.child_count()).filter_map(move |idx| {
+        let node = root.child(idx)?;
+        if node.kind() != "comment" {
+            return None
+        }
+        let comment = str::from_utf8(&code[node.byte_range()]).ok()?;
+        let target = disable_file_directive(comment)?;
+        Some((node, comment, target))
+    })
+}
+
+/// Check whether the `target` of a disable directive, a comma separated
+/// list of lint names, covers the lint `lint_name`.
+pub(crate) fn directive_covers(target: &str, lint_name: &str) -> bool {
+    target
+        .split(',')
+        .map(str::trim)
+        .any(|name| name == "all" || name == lint_name)
+}
+
+/// Iterate over the comment nodes directly preceding `node`, starting
+/// with the closest one.
+pub(crate) fn preceding_comments<'tree>(node: &Node<'tree>) -> impl Iterator<Item = Node<'tree>> {
+    iter::successors(node.prev_sibling(), Node::prev_sibling)
+        .take_while(|sibling| sibling.kind() == "comment")
+}
 
 /// Walk the syntax tree, checking if a comment node that disable the
 /// given lint is present.
 fn is_lint_disabled(lint_name: &str, mut node: Node, code: &[u8]) -> bool {
     loop {
-        // Walk all previous siblings of the current node.
-        if let Some(s) = node.prev_sibling() {
-            if s.kind() == "comment" {
-                let comment = &code[s.start_byte()..s.end_byte()];
-                if let Ok(comment) = str::from_utf8(comment) {
-                    // The comment node will still contain the actual
-                    // comment syntax, unfortunately.
-                    let comment = comment.trim_start_matches("//");
-                    let comment = comment.trim_start_matches("/*");
-                    let comment = comment.trim_end_matches("*/");
-                    let comment = comment.trim();
-
-                    if let Some(comment) = comment.strip_prefix("bpflint:") {
-                        let directive = comment.trim();
-                        match directive.strip_prefix("disable=") {
-                            Some("all") => break true,
-                            Some(disable) if disable == lint_name => break true,
-                            _ => (),
-                        }
-                    }
-                } else {
-                    // If it's not valid UTF-8 it can't be a comment for
-                    // us to consider.
-                    warn!(
-                        "encountered invalid UTF-8 in code comment at bytes `{}..{}`",
-                        s.start_byte(),
-                        s.end_byte()
-                    );
+        // Check all comments directly preceding the current node.
+        for s in preceding_comments(&node) {
+            let comment = &code[s.start_byte()..s.end_byte()];
+            if let Ok(comment) = str::from_utf8(comment) {
+                if disable_directive(comment)
+                    .is_some_and(|target| directive_covers(target, lint_name))
+                {
+                    return true
                 }
+            } else {
+                // If it's not valid UTF-8 it can't be a comment for us
+                // to consider.
+                warn!(
+                    "encountered invalid UTF-8 in code comment at bytes `{}..{}`",
+                    s.start_byte(),
+                    s.end_byte()
+                );
             }
         }
 
@@ -122,18 +446,225 @@ fn is_lint_disabled(lint_name: &str, mut node: Node, code: &[u8]) -> bool {
 }
 
 
-fn lint_impl(tree: &Tree, code: &[u8], lint_src: &str, lint_name: &str) -> Result<Vec<LintMatch>> {
-    let query =
-        Query::new(&LANGUAGE.into(), lint_src).with_context(|| "failed to compile lint query")?;
-    let mut query_cursor = QueryCursor::new();
+/// Look up the value of the property `key` in `settings`, if present.
+fn find_property<'prop>(
+    settings: &'prop [QueryProperty],
+    key: &str,
+    lint_name: &str,
+) -> Result<Option<&'prop str>> {
+    if let Some(setting) = settings.iter().find(|prop| &*prop.key == key) {
+        let value = setting
+            .value
+            .as_deref()
+            .with_context(|| format!("{lint_name}: `{key}` property has no value set"))?;
+        Ok(Some(value))
+    } else {
+        Ok(None)
+    }
+}
+
+
+/// Collect syntax errors present in the syntax tree as matches.
+pub(crate) fn syntax_errors(tree: &Tree) -> Vec<LintMatch> {
     let mut results = Vec::new();
-    let mut matches = query_cursor.matches(&query, tree.root_node(), code);
-    while let Some(m) = matches.next() {
-        for capture in m.captures {
-            if is_lint_disabled(lint_name, capture.node, code) {
-                continue;
+    let mut cursor = tree.walk();
+    let mut descend = true;
+    loop {
+        let node = cursor.node();
+        if descend && node.has_error() {
+            if node.is_error() || node.is_missing() {
+                let message = if node.is_missing() {
+                    format!("missing `{}`", node.kind())
+                } else {
+                    "unexpected syntax".to_string()
+                };
+                let r#match = LintMatch {
+                    lint_name: SYNTAX_ERROR.to_string(),
+                    message,
+                    severity: Severity::Error,
+                    range: Range::from(node.range()),
+                    fix: None,
+                };
+                let () = results.push(r#match);
+            } else if cursor.goto_first_child() {
+                continue
             }
+        }
+
+        if cursor.goto_next_sibling() {
+            descend = true;
+        } else if cursor.goto_parent() {
+            descend = false;
+        } else {
+            break results
+        }
+    }
+}
+
+
+/// Limits imposed on the execution of lint queries.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct QueryLimits {
+    /// The maximum number of in-progress matches per query.
+    match_limit: Option<u32>,
+    /// The point in time at which the time budget is exhausted.
+    #[cfg(not(target_arch = "wasm32"))]
+    deadline: Option<Instant>,
+}
+
+impl QueryLimits {
+    pub(crate) fn new(opts: &LintOpts) -> Result<Self> {
+        let LintOpts {
+            match_limit,
+            timeout,
+            ..
+        } = opts;
+
+        if let Some(limit) = match_limit {
+            if !(1..=65536).contains(limit) {
+                return Err(anyhow!(
+                    "invalid match limit {limit} (must be in the range 1..=65536)"
+                ))
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        let _timeout = timeout;
+
+        let slf = Self {
+            match_limit: *match_limit,
+            #[cfg(not(target_arch = "wasm32"))]
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+        };
+        Ok(slf)
+    }
+
+    /// Check whether the time budget has been exhausted.
+    pub(crate) fn timed_out(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            false
+        }
+    }
+}
+
+
+/// Create a match of the [`INCOMPLETE_RESULTS`] pseudo-lint, reporting
+/// that the results of lint `lint_name` are incomplete for the provided
+/// `reason`.
+fn incomplete_results(lint_name: &str, reason: &str) -> LintMatch {
+    LintMatch {
+        lint_name: INCOMPLETE_RESULTS.to_string(),
+        message: format!("results of lint `{lint_name}` may be incomplete: {reason}"),
+        severity: Severity::Warning,
+        range: Range {
+            bytes: 0..0,
+            start_point: Point::default(),
+            end_point: Point::default(),
+        },
+        fix: None,
+    }
+}
+
+
+/// Expand the `fix` property `template` of a lint for the match `m`.
+///
+/// The template may reference the text of any of the match's captures
+/// as `{<capture-name>}`. Literal braces are written as `{{` and `}}`.
+///
+/// No fix is produced if a referenced capture does not contain valid
+/// UTF-8, as it could not be represented faithfully.
+fn expand_fix(
+    template: &str,
+    query: &Query,
+    m: &QueryMatch<'_, '_>,
+    code: &[u8],
+    lint_name: &str,
+) -> Result<Option<String>> {
+    let mut fix = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(idx) = rest.find(['{', '}']) {
+        let () = fix.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        if let Some(remainder) = rest.strip_prefix("{{") {
+            let () = fix.push('{');
+            rest = remainder;
+        } else if let Some(remainder) = rest.strip_prefix("}}") {
+            let () = fix.push('}');
+            rest = remainder;
+        } else if let Some(remainder) = rest.strip_prefix('{') {
+            let end = remainder.find('}').with_context(|| {
+                format!("{lint_name}: `fix` property contains unterminated capture reference")
+            })?;
+            let name = &remainder[..end];
+            let capture = query
+                .capture_index_for_name(name)
+                .and_then(|idx| m.captures.iter().find(|capture| capture.index == idx))
+                .with_context(|| {
+                    format!("{lint_name}: `fix` property references unknown capture `{name}`")
+                })?;
+            let Ok(text) = capture.node.utf8_text(code) else {
+                return Ok(None)
+            };
+            let () = fix.push_str(text);
+            rest = &remainder[end + 1..];
+        } else {
+            return Err(anyhow!(
+                "{lint_name}: `fix` property contains unmatched `}}`"
+            ))
+        }
+    }
+    let () = fix.push_str(rest);
+    Ok(Some(fix))
+}
+
+
+/// Compile the lint query `lint_src`.
+pub(crate) fn compile_query(lint_src: &str) -> Result<Query> {
+    Query::new(&LANGUAGE.into(), lint_src).with_context(|| "failed to compile lint query")
+}
+
+/// Run the lint `query` on `tree`, adding matches to `results`.
+///
+/// If `bytes` is provided, only captures starting in this byte range
+/// are reported.
+pub(crate) fn lint_impl(
+    tree: &Tree,
+    code: &[u8],
+    query: &Query,
+    lint_name: &str,
+    bytes: Option<ops::Range<usize>>,
+    limits: &QueryLimits,
+    results: &mut LintResults,
+) -> Result<()> {
+    let mut query_cursor = QueryCursor::new();
+    if let Some(limit) = limits.match_limit {
+        let () = query_cursor.set_match_limit(limit);
+    }
+    if let Some(bytes) = &bytes {
+        let _cursor = query_cursor.set_byte_range(bytes.clone());
+    }
 
+    // A lint disabled for the entire file is not reported anywhere in
+    // it.
+    let file_disabled =
+        file_directives(tree, code).any(|(_, _, target)| directive_covers(target, lint_name));
+
+    let mut timed_out = false;
+    let mut progress = |_state: &QueryCursorState| {
+        timed_out = limits.timed_out();
+        timed_out
+    };
+    let options = QueryCursorOptions::new().progress_callback(&mut progress);
+    let mut matches = query_cursor.matches_with_options(query, tree.root_node(), code, options);
+    while let Some(m) = matches.next() {
+        for capture in m.captures {
             // SANITY: It would be a tree-sitter bug if the capture
             //         index does not map to a valid capture name.
             let capture_name = query.capture_names()[capture.index as usize];
@@ -143,48 +674,137 @@ fn lint_impl(tree: &Tree, code: &[u8], lint_src: &str, lint_name: &str) -> Resul
                 continue
             }
 
+            if let Some(bytes) = &bytes {
+                if !bytes.contains(&capture.node.start_byte()) {
+                    continue
+                }
+            }
+
+            if file_disabled || is_lint_disabled(lint_name, capture.node, code) {
+                results.suppressed += 1;
+                continue;
+            }
+
             let settings = query.property_settings(m.pattern_index);
-            let setting = settings
-                .iter()
-                .find(|prop| &*prop.key == "message")
+            let message = find_property(settings, "message", lint_name)?
                 .with_context(|| format!("{lint_name}: failed to find `message` property"))?;
+            let severity = find_property(settings, "severity", lint_name)?
+                .map(Severity::from_str)
+                .transpose()
+                .with_context(|| format!("{lint_name}: `severity` property is invalid"))?
+                .unwrap_or_default();
+            let fix = find_property(settings, "fix", lint_name)?
+                .map(|template| expand_fix(template, query, m, code, lint_name))
+                .transpose()?
+                .flatten();
 
             let r#match = LintMatch {
                 lint_name: lint_name.to_string(),
-                message: setting
-                    .value
-                    .as_ref()
-                    .with_context(|| format!("{lint_name}: `message` property has no value set"))?
-                    .to_string(),
+                message: message.to_string(),
+                severity,
                 range: Range::from(capture.node.range()),
+                fix,
             };
-            let () = results.push(r#match);
+            let () = results.matches.push(r#match);
         }
     }
 
     if query_cursor.did_exceed_match_limit() {
-        warn!("query exceeded maximum number of in-progress captures");
+        warn!("{lint_name}: query exceeded maximum number of in-progress captures");
+        let () = results.exceeded_match_limit.push(lint_name.to_string());
+        let reason = format!(
+            "query exceeded the limit of {} in-progress matches",
+            query_cursor.match_limit()
+        );
+        let () = results.matches.push(incomplete_results(lint_name, &reason));
     }
-    Ok(results)
+
+    if timed_out {
+        let () = results
+            .matches
+            .push(incomplete_results(lint_name, "time budget exhausted"));
+    }
+    Ok(())
 }
 
-fn lint_multi(code: &[u8], lints: &[(&str, &str)]) -> Result<Vec<LintMatch>> {
+/// Parse `code`, reusing unchanged parts of `old_tree`, if provided.
+pub(crate) fn reparse(code: &[u8], old_tree: Option<&Tree>) -> Result<Tree> {
     let mut parser = Parser::new();
     let () = parser
         .set_language(&LANGUAGE.into())
         .context("failed to load C parser")?;
-    let tree = parser
-        .parse(code, None)
-        .context("failed to provided source code")?;
-    let mut results = Vec::new();
-    for (lint_name, lint_src) in lints {
-        let matches = lint_impl(&tree, code, lint_src, lint_name)?;
-        let () = results.extend(matches);
+    parser
+        .parse(code, old_tree)
+        .context("failed to parse provided source code")
+}
+
+/// Parse BPF C source code `code` into a syntax tree.
+///
+/// The tree can be used for computing edits suppressing lint matches
+/// via [`suppression_edit`][crate::suppression_edit].
+pub fn parse(code: &[u8]) -> Result<Tree> {
+    reparse(code, None)
+}
+
+fn lint_multi(code: &[u8], lints: &[(&str, &str)], opts: &LintOpts) -> Result<LintResults> {
+    let mut parser = Parser::new();
+    let () = parser
+        .set_language(&LANGUAGE.into())
+        .context("failed to load C parser")?;
+    let limits = QueryLimits::new(opts)?;
+    let (tree, parse_time) = measure(opts.timings, || parser.parse(code, None));
+    let tree = tree.context("failed to provided source code")?;
+    let mut timings = parse_time.map(|parse| LintTimings {
+        parse,
+        lints: Vec::with_capacity(lints.len()),
+    });
+    let mut results = LintResults::default();
+    if opts.syntax_errors {
+        results.matches = syntax_errors(&tree);
+    }
+    // Compile queries up front, so that reported timings cover only
+    // their execution.
+    let queries = lints
+        .iter()
+        .filter(|(lint_name, _)| {
+            !opts
+                .disabled_lints
+                .iter()
+                .any(|disabled| disabled == lint_name)
+        })
+        .map(|(lint_name, lint_src)| {
+            let query = compile_query(lint_src)
+                .with_context(|| format!("{lint_name}: failed to load lint"))?;
+            Ok((*lint_name, query))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for (lint_name, query) in &queries {
+        if limits.timed_out() {
+            let () = results
+                .matches
+                .push(incomplete_results(lint_name, "time budget exhausted"));
+            continue
+        }
+
+        let (result, lint_time) = measure(opts.timings, || {
+            lint_impl(&tree, code, query, lint_name, None, &limits, &mut results)
+        });
+        let () = result?;
+        if let (Some(timings), Some(lint_time)) = (&mut timings, lint_time) {
+            let () = timings.lints.push((lint_name.to_string(), lint_time));
+        }
     }
+    results.timings = timings;
 
-    // Sort results to ensure more consistent reporting with ascending
-    // lines.
-    let () = results.sort_by(|match1, match2| {
+    let () = sort_matches(&mut results.matches);
+    Ok(results)
+}
+
+/// Sort `matches` to ensure more consistent reporting with ascending
+/// lines.
+pub(crate) fn sort_matches(matches: &mut [LintMatch]) {
+    matches.sort_by(|match1, match2| {
         // NB: We use an ad-hoc comparison rather than a proper
         // `PartialOrd` impl for `Range`, because the latter is a bit
         // harder to do correctly.
@@ -193,8 +813,7 @@ fn lint_multi(code: &[u8], lints: &[(&str, &str)]) -> Result<Vec<LintMatch>> {
             .start_point
             .cmp(&match2.range.start_point)
             .then_with(|| match1.range.end_point.cmp(&match2.range.end_point))
-    });
-    Ok(results)
+    })
 }
 
 /// Lint code using the default set of lints.
@@ -204,7 +823,20 @@ fn lint_multi(code: &[u8], lints: &[(&str, &str)]) -> Result<Vec<LintMatch>> {
 /// - `code` is the source code in question, for example as read from a
 ///   file
 pub fn lint(code: &[u8]) -> Result<Vec<LintMatch>> {
-    lint_multi(code, &lints::LINTS)
+    let results = lint_opts(code, &LintOpts::default())?;
+    Ok(results.matches)
+}
+
+/// Lint code using the default set of lints, with the provided options.
+///
+/// Matches are reported in source code order, alongside additional
+/// information about the linting process.
+///
+/// - `code` is the source code in question, for example as read from a
+///   file
+/// - `opts` specifies the linting options
+pub fn lint_opts(code: &[u8], opts: &LintOpts) -> Result<LintResults> {
+    lint_multi(code, &lints::LINTS, opts)
 }
 
 
@@ -240,7 +872,8 @@ mod tests {
               function: (identifier) @function (#eq? @function "test_fn")
           )
         "# };
-        let err = lint_multi(code.as_bytes(), &[("test_fn", lint)]).unwrap_err();
+        let err =
+            lint_multi(code.as_bytes(), &[("test_fn", lint)], &LintOpts::default()).unwrap_err();
         assert_eq!(
             err.to_string(),
             "test_fn: failed to find `message` property",
@@ -261,10 +894,19 @@ mod tests {
         let code = indoc! { r#"
           bar();
         "# };
-        let matches = lint_multi(code.as_bytes(), &[("bar", lint_bar)]).unwrap();
+        let matches = lint_multi(code.as_bytes(), &[("bar", lint_bar)], &LintOpts::default())
+            .unwrap()
+            .matches;
         assert!(matches.is_empty(), "{matches:?}");
     }
 
+    fn is_lower_ascii_slug(s: &str) -> bool {
+        !s.is_empty()
+            && !s.starts_with('-')
+            && !s.ends_with('-')
+            && s.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+    }
+
     /// Check that `tree-sitter` queries represented by built-in lints
     /// exhibit the expected set of properties.
     #[test]
@@ -294,9 +936,365 @@ mod tests {
                 !['.', '!', '?'].contains(&last),
                 "`message` property of lint `{name}` should be concise and not a fully blown sentence with punctuation"
             );
+
+            let meta = QueryMeta::new(name, code).unwrap();
+            assert!(
+                is_lower_ascii_slug(&meta.category),
+                "`category` property of lint `{name}` is not a lower case slug: {}",
+                meta.category
+            );
+
+            if let Some(severity) = find_property(settings, "severity", name).unwrap() {
+                assert!(
+                    Severity::from_str(severity).is_ok(),
+                    "`severity` property of lint `{name}` is invalid: {severity}"
+                );
+            }
+        }
+    }
+
+    /// Check that the documentation of built-in lints is well-formed.
+    #[test]
+    fn validate_lint_docs() {
+        for ((name, _code), doc) in lints::LINTS.iter().zip(lints::DOCS) {
+            let doc = LintDoc::parse(name, doc).unwrap();
+            assert!(
+                !doc.description.is_empty(),
+                "lint `{name}` lacks description"
+            );
+            assert!(!doc.rationale.is_empty(), "lint `{name}` lacks rationale");
         }
     }
 
+    /// Check that we can convert [`Severity`] objects to and from
+    /// strings.
+    #[test]
+    fn severity_conversion() {
+        for severity in [Severity::Note, Severity::Warning, Severity::Error] {
+            assert_eq!(Severity::from_str(&severity.to_string()).unwrap(), severity);
+        }
+
+        assert!(Severity::Note < Severity::Warning);
+        assert!(Severity::Warning < Severity::Error);
+        assert_eq!(Severity::default(), Severity::Warning);
+
+        let err = Severity::from_str("fatal").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid severity `fatal` (expected one of `note`, `warning`, `error`)"
+        );
+    }
+
+    /// Check that a lint's `severity` property is honored.
+    #[test]
+    fn severity_property() {
+        let code = indoc! { r#"
+          foo();
+        "# };
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].severity, Severity::Warning);
+
+        let lint = indoc! { r#"
+          (call_expression
+              function: (identifier) @function (#eq? @function "foo")
+              (#set! "message" "foo")
+              (#set! "severity" "error")
+          )
+        "# };
+        let matches = lint_multi(code.as_bytes(), &[("foo", lint)], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].severity, Severity::Error);
+
+        let lint = indoc! { r#"
+          (call_expression
+              function: (identifier) @function (#eq? @function "foo")
+              (#set! "message" "foo")
+              (#set! "severity" "bogus")
+          )
+        "# };
+        let err = lint_multi(code.as_bytes(), &[("foo", lint)], &LintOpts::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "foo: `severity` property is invalid",
+            "{err}"
+        );
+    }
+
+    /// Check that lints can be disabled via [`LintOpts`].
+    #[test]
+    fn lint_disabling_opts() {
+        let lint_bar = indoc! { r#"
+          (call_expression
+              function: (identifier) @function (#eq? @function "bar")
+              (#set! "message" "bar")
+          )
+        "# };
+        let code = indoc! { r#"
+          bar();
+          foo();
+        "# };
+        let opts = LintOpts {
+            disabled_lints: vec!["foo".to_string()],
+            ..Default::default()
+        };
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO, ("bar", lint_bar)], &opts)
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "bar");
+    }
+
+    /// Check that timings are only reported if requested.
+    #[test]
+    fn timing_reporting() {
+        let code = indoc! { r#"
+          foo();
+        "# };
+        let results = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default()).unwrap();
+        assert!(results.timings.is_none());
+
+        let opts = LintOpts {
+            timings: true,
+            disabled_lints: vec!["bar".to_string()],
+            ..Default::default()
+        };
+        let lints = [LINT_FOO, ("bar", LINT_FOO.1)];
+        let results = lint_multi(code.as_bytes(), &lints, &opts).unwrap();
+        let timings = results.timings.unwrap();
+        assert_eq!(timings.lints.len(), 1);
+        assert_eq!(timings.lints[0].0, "foo");
+        assert!(results.exceeded_match_limit.is_empty());
+    }
+
+    /// Check that lint timings cover only the execution of enabled
+    /// lints, but not the compilation of their queries.
+    #[test]
+    fn timing_excludes_compilation() {
+        let code = indoc! { r#"
+          foo();
+        "# };
+        // A query that is comparably expensive to compile, but cheap to
+        // execute on the code above.
+        let lint_src = (0..100)
+            .map(|i| {
+                format!(
+                    r#"(call_expression function: (identifier) @f{i} (#eq? @f{i} "f{i}") (#set! "message" "f{i}"))"#
+                )
+            })
+            .collect::<String>();
+        let opts = LintOpts {
+            timings: true,
+            disabled_lints: vec!["invalid".to_string()],
+            ..Default::default()
+        };
+        // The disabled lint is invalid, but should not even get
+        // compiled.
+        let lints = [("slow", lint_src.as_str()), ("invalid", "(")];
+        let results = lint_multi(code.as_bytes(), &lints, &opts).unwrap();
+        let timings = results.timings.unwrap();
+        assert_eq!(timings.lints.len(), 1);
+        assert_eq!(timings.lints[0].0, "slow");
+
+        let start = Instant::now();
+        let _query = compile_query(&lint_src).unwrap();
+        let compile_time = start.elapsed();
+        assert!(
+            timings.lints[0].1 < compile_time,
+            "{:?} >= {compile_time:?}",
+            timings.lints[0].1
+        );
+    }
+
+    /// Check that exceeding the time budget is reported.
+    #[test]
+    fn timeout_reporting() {
+        let code = indoc! { r#"
+          foo();
+        "# };
+        let opts = LintOpts {
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let lints = [LINT_FOO, ("bar", LINT_FOO.1)];
+        let matches = lint_multi(code.as_bytes(), &lints, &opts).unwrap().matches;
+        assert_eq!(matches.len(), 2, "{matches:?}");
+        assert_eq!(matches[0].lint_name, INCOMPLETE_RESULTS);
+        assert_eq!(
+            matches[0].message,
+            "results of lint `foo` may be incomplete: time budget exhausted"
+        );
+        assert_eq!(matches[1].lint_name, INCOMPLETE_RESULTS);
+
+        let opts = LintOpts {
+            timeout: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let matches = lint_multi(code.as_bytes(), &lints, &opts).unwrap().matches;
+        assert_eq!(matches.len(), 2, "{matches:?}");
+        assert!(
+            matches
+                .iter()
+                .all(|m| m.lint_name == "foo" || m.lint_name == "bar")
+        );
+    }
+
+    /// Check that exceeding the match limit is reported.
+    #[test]
+    fn match_limit_reporting() {
+        let code = indoc! { r#"
+          foo(a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p, q, r, s, t);
+        "# };
+        let lint = indoc! { r#"
+          (argument_list
+              (identifier) @a
+              (identifier) @b
+              (#set! "message" "pair")
+          )
+        "# };
+        let opts = LintOpts {
+            match_limit: Some(1),
+            ..Default::default()
+        };
+        let results = lint_multi(code.as_bytes(), &[("pair", lint)], &opts).unwrap();
+        assert_eq!(results.exceeded_match_limit, vec!["pair".to_string()]);
+        let incomplete = results
+            .matches
+            .iter()
+            .filter(|m| m.lint_name == INCOMPLETE_RESULTS)
+            .collect::<Vec<_>>();
+        assert_eq!(incomplete.len(), 1, "{incomplete:?}");
+        assert_eq!(
+            incomplete[0].message,
+            "results of lint `pair` may be incomplete: query exceeded the limit of 1 in-progress matches"
+        );
+
+        let results = lint_multi(code.as_bytes(), &[("pair", lint)], &LintOpts::default()).unwrap();
+        assert!(results.exceeded_match_limit.is_empty());
+
+        for limit in [0, 65537] {
+            let opts = LintOpts {
+                match_limit: Some(limit),
+                ..Default::default()
+            };
+            let err = lint_multi(code.as_bytes(), &[LINT_FOO], &opts).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("invalid match limit {limit} (must be in the range 1..=65536)")
+            );
+        }
+    }
+
+    /// Check that suggested fixes are expanded correctly.
+    #[test]
+    fn fix_expansion() {
+        let lint = indoc! { r#"
+          (call_expression
+              function: (identifier) @__function (#eq? @__function "foo")
+              arguments: (argument_list (identifier) @__arg)
+              (#set! "message" "foo")
+              (#set! "fix" "{{ bar({__arg}, {__function}); }}")
+          ) @call
+        "# };
+        let code = indoc! { r#"
+          foo(x);
+        "# };
+        let matches = lint_multi(code.as_bytes(), &[("foo", lint)], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(&code[matches[0].range.bytes.clone()], "foo(x)");
+        assert_eq!(matches[0].fix.as_deref(), Some("{ bar(x, foo); }"));
+
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches[0].fix, None);
+
+        for (template, error) in [
+            (
+                "{__bogus}",
+                "foo: `fix` property references unknown capture `__bogus`",
+            ),
+            (
+                "{__arg",
+                "foo: `fix` property contains unterminated capture reference",
+            ),
+            ("__arg}", "foo: `fix` property contains unmatched `}`"),
+        ] {
+            let lint = format!(
+                r#"
+(call_expression
+    function: (identifier) @__function (#eq? @__function "foo")
+    arguments: (argument_list (identifier) @__arg)
+    (#set! "message" "foo")
+    (#set! "fix" "{template}")
+) @call
+                "#
+            );
+            let err =
+                lint_multi(code.as_bytes(), &[("foo", &lint)], &LintOpts::default()).unwrap_err();
+            assert_eq!(err.to_string(), error, "{template}");
+        }
+
+        // Captures not containing valid UTF-8 can't be represented in
+        // a fix.
+        let lint = indoc! { r#"
+          (call_expression
+              function: (identifier) @__function (#eq? @__function "foo")
+              arguments: (argument_list (string_literal) @__arg)
+              (#set! "message" "foo")
+              (#set! "fix" "bar({__arg});")
+          ) @call
+        "# };
+        let code = b"foo(\"\xff\");\n";
+        let matches = lint_multi(code, &[("foo", lint)], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(matches[0].fix, None);
+    }
+
+    /// Check that syntax errors are reported only if requested.
+    #[test]
+    fn syntax_error_reporting() {
+        let code = indoc! { r#"
+          void test_fn(void) {
+              foo();
+        "# };
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "foo");
+
+        let opts = LintOpts {
+            syntax_errors: true,
+            ..Default::default()
+        };
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &opts)
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 2, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "foo");
+        assert_eq!(matches[1].lint_name, SYNTAX_ERROR);
+        assert_eq!(matches[1].message, "missing `}`");
+        assert_eq!(matches[1].severity, Severity::Error);
+
+        let code = indoc! { r#"
+          foo();
+        "# };
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &opts)
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1, "{matches:?}");
+        assert_eq!(matches[0].lint_name, "foo");
+    }
+
     /// Check that some basic linting works as expected.
     #[test]
     fn basic_linting() {
@@ -318,9 +1316,13 @@ mod tests {
         let LintMatch {
             lint_name,
             message,
+            severity,
             range,
+            fix,
         } = &matches[0];
         assert_eq!(lint_name, "probe-read");
+        assert_eq!(*severity, Severity::Warning);
+        assert_eq!(fix.as_deref(), Some("bpf_probe_read_kernel"));
         assert!(
             message.starts_with("bpf_probe_read() is deprecated"),
             "{message}"
@@ -343,7 +1345,13 @@ mod tests {
           bar();
           foo();
         "# };
-        let matches = lint_multi(code.as_bytes(), &[LINT_FOO, ("bar", lint_bar)]).unwrap();
+        let matches = lint_multi(
+            code.as_bytes(),
+            &[LINT_FOO, ("bar", lint_bar)],
+            &LintOpts::default(),
+        )
+        .unwrap()
+        .matches;
         assert_eq!(matches.len(), 2);
         assert_eq!(matches[0].lint_name, "bar");
         assert_eq!(matches[1].lint_name, "foo");
@@ -359,9 +1367,16 @@ mod tests {
           foo();
           // bpflint: disable=all
           foo();
+          /* bpflint: disable=bar, foo */
+          foo();
+          /* bpflint: disable=foo */
+          /* Call foo. */
+          // bpflint: disable=bar
+          foo();
         "# };
-        let matches = lint_multi(code.as_bytes(), &[LINT_FOO]).unwrap();
-        assert_eq!(matches.len(), 0, "{matches:?}");
+        let results = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default()).unwrap();
+        assert_eq!(results.matches.len(), 0, "{results:?}");
+        assert_eq!(results.suppressed, 5);
     }
 
     /// Check that we can disable lints by name for a given block.
@@ -375,7 +1390,9 @@ mod tests {
               }
           }
         "# };
-        let matches = lint_multi(code.as_bytes(), &[LINT_FOO]).unwrap();
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default())
+            .unwrap()
+            .matches;
         assert_eq!(matches.len(), 0, "{matches:?}");
 
         let code = indoc! { r#"
@@ -384,10 +1401,40 @@ mod tests {
               foo();
           }
         "# };
-        let matches = lint_multi(code.as_bytes(), &[LINT_FOO]).unwrap();
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default())
+            .unwrap()
+            .matches;
         assert_eq!(matches.len(), 0, "{matches:?}");
     }
 
+    /// Check that we can disable lints by name for an entire file.
+    #[test]
+    fn lint_disabling_file() {
+        let code = indoc! { r#"
+          foo();
+          void test_fn(void) {
+              // bpflint: disable-file=bar
+              foo();
+          }
+          /* bpflint: disable-file=bar, foo */
+        "# };
+        let results = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default()).unwrap();
+        assert_eq!(results.matches.len(), 0, "{results:?}");
+        assert_eq!(results.suppressed, 2);
+
+        // Only top-level comments are considered.
+        let code = indoc! { r#"
+          void test_fn(void) {
+              // bpflint: disable-file=foo
+              foo();
+          }
+        "# };
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 1, "{matches:?}");
+    }
+
     /// Check that erroneous disabling syntax is not accidentally recognized.
     #[test]
     fn lint_invalid_disabling() {
@@ -402,6 +1449,11 @@ mod tests {
           foo();
           // bpflint: disable=bar
           foo();
+          // bpflint: disable=foobar,bar
+          foo();
+          /* bpflint: disable=foo */
+          bar();
+          foo();
 
           void test_fn(void) {
               /* bpflint: disable=foo */
@@ -409,7 +1461,9 @@ mod tests {
               foo();
           }
         "# };
-        let matches = lint_multi(code.as_bytes(), &[LINT_FOO]).unwrap();
-        assert_eq!(matches.len(), 6, "{matches:?}");
+        let matches = lint_multi(code.as_bytes(), &[LINT_FOO], &LintOpts::default())
+            .unwrap()
+            .matches;
+        assert_eq!(matches.len(), 8, "{matches:?}");
     }
 }
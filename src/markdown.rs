@@ -0,0 +1,395 @@
+use std::borrow::Cow;
+use std::io;
+use std::mem;
+
+use anyhow::Result;
+
+use crate::LintMatch;
+use crate::report::Opts;
+use crate::report::caret_span;
+use crate::report::render_line;
+use crate::report::snippet_rows;
+use crate::reporter::Reporter;
+use crate::reporter::SourceFile;
+
+
+/// The number of matches of a lint in a file above which they are
+/// collapsed into a `<details>` element.
+const COLLAPSE_THRESHOLD: usize = 3;
+
+
+/// Escape `text` for inclusion in Markdown text, such that it is
+/// rendered verbatim.
+fn escape(text: &str) -> Cow<'_, str> {
+    fn needs_escape(c: char) -> bool {
+        matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~' | '&'
+        )
+    }
+
+    if !text.contains(needs_escape) {
+        return Cow::Borrowed(text)
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        if needs_escape(c) {
+            let () = escaped.push('\\');
+        }
+        let () = escaped.push(c);
+    }
+    Cow::Owned(escaped)
+}
+
+/// Create a fence of backticks for `text`, that is longer than any
+/// sequence of backticks contained in it and at least `min` long.
+fn fence(text: &str, min: usize) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    "`".repeat(min.max(longest + 1))
+}
+
+/// Format `text` as inline code.
+fn code_span(text: &str) -> String {
+    let fence = fence(text, 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{fence} {text} {fence}")
+    } else {
+        format!("{fence}{text}{fence}")
+    }
+}
+
+
+/// A [`Reporter`] producing Markdown, as suitable for posting as a
+/// comment on a pull request.
+///
+/// Matches are grouped by file and, within a file, by lint. Each match
+/// is accompanied by a code snippet in a fenced `c` block, including
+/// context lines as configured in the provided [`Opts`]. Lints with
+/// more than three matches in a file are collapsed into a `<details>`
+/// element. Locations are reported as 1-based lines and columns, unlike
+/// in terminal style reports, which use zero-based ones.
+///
+/// # Example
+/// ````markdown
+/// ### `example.bpf.c`
+///
+/// #### `probe-read`
+///
+/// **warning** at 43:5: bpf\_probe\_read() is deprecated
+///
+/// ```c
+/// 43 |     bpf_probe_read(event.comm, TASK_COMM_LEN, prev->comm);
+///    |     ^^^^^^^^^^^^^^
+/// ```
+/// ````
+#[derive(Debug)]
+pub struct MarkdownReporter<W> {
+    /// The writer to which to write the report.
+    writer: W,
+    /// The options used for rendering code snippets.
+    opts: Opts,
+    /// The number of matches reported so far.
+    count: usize,
+    /// The matches of the current file, grouped by lint in order of
+    /// their first occurrence.
+    groups: Vec<(String, Vec<LintMatch>)>,
+}
+
+impl<W> MarkdownReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`MarkdownReporter`] writing to `writer`.
+    pub fn new(writer: W, opts: Opts) -> Self {
+        Self {
+            writer,
+            opts,
+            count: 0,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Render the code snippet for `match` in `file`, with line
+    /// numbers.
+    fn render_snippet(&self, file: &SourceFile<'_>, r#match: &LintMatch) -> Vec<String> {
+        let code = file.code();
+        let index = file.index();
+        let range = &r#match.range;
+        let rows = snippet_rows(r#match, index, &self.opts);
+        let tab_width = usize::from(self.opts.tab_width);
+        let width = rows.end.to_string().len();
+
+        let mut snippet = Vec::new();
+        for row in rows {
+            let Some(line) = index.line(code, row) else {
+                break
+            };
+            let () = snippet.push(format!(
+                "{:>width$} | {}",
+                row + 1,
+                render_line(line, tab_width)
+            ));
+
+            if row == range.start_point.row && range.start_point.row == range.end_point.row {
+                let (indent, carets) =
+                    caret_span(line, range.start_point.col, range.end_point.col, tab_width);
+                let () = snippet.push(format!("{:width$} | {:indent$}{:^<carets$}", "", "", ""));
+            }
+        }
+        snippet
+    }
+
+    /// Write the report for `match` in `file`.
+    fn write_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let LintMatch {
+            lint_name: _,
+            message,
+            severity,
+            range,
+            fix: _,
+        } = r#match;
+
+        writeln!(
+            self.writer,
+            "**{}** at {}:{}: {}",
+            severity.as_str(),
+            range.start_point.row + 1,
+            range.start_point.col + 1,
+            escape(message),
+        )?;
+        writeln!(self.writer)?;
+
+        if !range.bytes.is_empty() {
+            let snippet = self.render_snippet(file, r#match).join("\n");
+            let fence = fence(&snippet, 3);
+            writeln!(self.writer, "{fence}c\n{snippet}\n{fence}")?;
+            writeln!(self.writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W> Reporter for MarkdownReporter<W>
+where
+    W: io::Write,
+{
+    fn begin_run(&mut self) -> Result<()> {
+        self.count = 0;
+        Ok(())
+    }
+
+    fn begin_file(&mut self, _file: &SourceFile<'_>) -> Result<()> {
+        let () = self.groups.clear();
+        Ok(())
+    }
+
+    fn report_match(&mut self, _file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        self.count += 1;
+        if let Some((_, matches)) = self
+            .groups
+            .iter_mut()
+            .find(|(lint_name, _)| *lint_name == r#match.lint_name)
+        {
+            let () = matches.push(r#match.clone());
+        } else {
+            let () = self
+                .groups
+                .push((r#match.lint_name.clone(), vec![r#match.clone()]));
+        }
+        Ok(())
+    }
+
+    fn end_file(&mut self, file: &SourceFile<'_>) -> Result<()> {
+        if self.groups.is_empty() {
+            return Ok(())
+        }
+
+        let path = file.path().display().to_string();
+        writeln!(self.writer, "### {}", code_span(&path))?;
+        writeln!(self.writer)?;
+
+        let groups = mem::take(&mut self.groups);
+        for (lint_name, matches) in &groups {
+            let collapse = matches.len() > COLLAPSE_THRESHOLD;
+            writeln!(self.writer, "#### {}", code_span(lint_name))?;
+            writeln!(self.writer)?;
+            if collapse {
+                writeln!(self.writer, "<details>")?;
+                writeln!(self.writer, "<summary>{} matches</summary>", matches.len())?;
+                writeln!(self.writer)?;
+            }
+            for r#match in matches {
+                let () = self.write_match(file, r#match)?;
+            }
+            if collapse {
+                writeln!(self.writer, "</details>")?;
+                writeln!(self.writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        if self.count == 0 {
+            writeln!(self.writer, "No lint matches found.")?;
+        }
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+    use crate::Severity;
+
+
+    /// Check that text and code are escaped and fenced correctly.
+    #[test]
+    fn escaping() {
+        assert!(matches!(escape("foo bar"), Cow::Borrowed(_)));
+        assert_eq!(escape("__uint(<a>) * `b`"), r"\_\_uint(\<a\>) \* \`b\`");
+        assert_eq!(fence("foo", 3), "```");
+        assert_eq!(fence("a ```` b", 3), "`````");
+        assert_eq!(code_span("a.bpf.c"), "`a.bpf.c`");
+        assert_eq!(code_span("`a`b``"), "``` `a`b`` ```");
+    }
+
+    /// Check that we produce the expected Markdown report.
+    #[test]
+    fn markdown_reporting() {
+        let code = indoc! { r#"
+          int main() {
+            foo(); foo(); foo(); foo();
+            bar();
+            return 0;
+          }
+        "# };
+        let m = |lint_name: &str, row: usize, col: usize| LintMatch {
+            lint_name: lint_name.to_string(),
+            message: format!("{lint_name}_ is *bad*"),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 13 + 30 * (row - 1) + col..13 + 30 * (row - 1) + col + 3,
+                start_point: Point { row, col },
+                end_point: Point { row, col: col + 3 },
+            },
+            fix: None,
+        };
+        let matches = [
+            m("foo", 1, 2),
+            m("bar", 2, 2),
+            m("foo", 1, 9),
+            m("foo", 1, 16),
+            m("foo", 1, 23),
+        ];
+        let opts = Opts {
+            extra_lines: Some((0, 1)),
+            ..Default::default()
+        };
+
+        let mut reporter = MarkdownReporter::new(Vec::new(), opts);
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("a.bpf.c"), code.as_bytes());
+        let () = reporter.report_file(&file, &matches[1..2]).unwrap();
+        let file = SourceFile::new(Path::new("clean.bpf.c"), b"");
+        let () = reporter.report_file(&file, &[]).unwrap();
+        let file = SourceFile::new(Path::new("b.bpf.c"), code.as_bytes());
+        let () = reporter.report_file(&file, &matches).unwrap();
+        let () = reporter.end_run().unwrap();
+
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        let expected = indoc! { r#"
+          ### `a.bpf.c`
+
+          #### `bar`
+
+          **warning** at 3:3: bar\_ is \*bad\*
+
+          ```c
+          3 |   bar();
+            |   ^^^
+          4 |   return 0;
+          ```
+
+          ### `b.bpf.c`
+
+          #### `foo`
+
+          <details>
+          <summary>4 matches</summary>
+
+          **warning** at 2:3: foo\_ is \*bad\*
+
+          ```c
+          2 |   foo(); foo(); foo(); foo();
+            |   ^^^
+          3 |   bar();
+          ```
+
+          **warning** at 2:10: foo\_ is \*bad\*
+
+          ```c
+          2 |   foo(); foo(); foo(); foo();
+            |          ^^^
+          3 |   bar();
+          ```
+
+          **warning** at 2:17: foo\_ is \*bad\*
+
+          ```c
+          2 |   foo(); foo(); foo(); foo();
+            |                 ^^^
+          3 |   bar();
+          ```
+
+          **warning** at 2:24: foo\_ is \*bad\*
+
+          ```c
+          2 |   foo(); foo(); foo(); foo();
+            |                        ^^^
+          3 |   bar();
+          ```
+
+          </details>
+
+          #### `bar`
+
+          **warning** at 3:3: bar\_ is \*bad\*
+
+          ```c
+          3 |   bar();
+            |   ^^^
+          4 |   return 0;
+          ```
+
+        "# };
+        assert_eq!(report, expected);
+
+        // An empty report still says something.
+        let mut reporter = MarkdownReporter::new(Vec::new(), Opts::default());
+        let () = reporter.begin_run().unwrap();
+        let () = reporter.end_run().unwrap();
+        assert_eq!(reporter.into_inner(), b"No lint matches found.\n");
+    }
+}
@@ -1,16 +1,71 @@
+use std::borrow::Cow;
 use std::io;
+use std::ops;
 use std::path::Path;
 
 use anyhow::Result;
 
+use unicode_width::UnicodeWidthChar as _;
+
 use crate::LintMatch;
+use crate::Severity;
+use crate::lines::LineIndex;
 use crate::lines::Lines;
 
+
+/// ANSI escape sequences used for colorizing reports.
+mod style {
+    pub const RESET: &str = "\x1b[0m";
+    pub const BOLD: &str = "\x1b[1m";
+    pub const ERROR: &str = "\x1b[1;31m";
+    pub const WARNING: &str = "\x1b[1;33m";
+    pub const NOTE: &str = "\x1b[1;36m";
+    pub const GUTTER: &str = "\x1b[1;34m";
+}
+
+
+/// The maximum number of lines of a multi-line span to display before
+/// eliding lines in its middle.
+const MAX_SPAN_LINES: usize = 8;
+/// The number of lines following the first one to display of an elided
+/// multi-line span.
+const SPAN_HEAD_LINES: usize = 3;
+/// The number of lines preceding the last one to display of an elided
+/// multi-line span.
+const SPAN_TAIL_LINES: usize = 2;
+/// The marker used in place of a row number for elided lines.
+const ELISION: &str = "...";
+
+/// The default distance between tab stops, in columns.
+const DEFAULT_TAB_WIDTH: u8 = 4;
+
+
 /// Configuration options for terminal reporting.
-#[derive(Default, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Opts {
     /// Extra context lines: (`lines_before`, `lines_after`).
     pub extra_lines: Option<(u8, u8)>,
+    /// Whether to colorize the report using ANSI escape sequences.
+    pub color: bool,
+    /// The distance between tab stops, in columns, used when expanding
+    /// tabs in code snippets.
+    pub tab_width: u8,
+    /// Whether to report matches whose context windows overlap or
+    /// touch as part of a single code snippet.
+    ///
+    /// This setting only affects [`report_terminal_matches`].
+    pub group_matches: bool,
+}
+
+impl Default for Opts {
+    fn default() -> Self {
+        Self {
+            extra_lines: None,
+            color: false,
+            tab_width: DEFAULT_TAB_WIDTH,
+            group_matches: false,
+        }
+    }
 }
 
 impl Opts {
@@ -29,87 +84,263 @@ impl Opts {
             Some((_, after)) => usize::from(after),
         }
     }
+
+    /// Wrap `text` in the ANSI escape sequence `style`, if coloring is
+    /// enabled.
+    fn paint<'text>(&self, style: &str, text: &'text str) -> Cow<'text, str> {
+        if self.color && !text.is_empty() {
+            Cow::Owned(format!("{style}{text}{}", style::RESET))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
 }
 
-/// Find the byte position of the start of a specific line number (0-indexed)
-fn find_line_start_by_row(code: &[u8], target_row: usize) -> usize {
-    if target_row == 0 {
-        return 0;
+
+/// Calculate the number of columns a tab at display column `col`
+/// advances the cursor by.
+fn tab_advance(col: usize, tab_width: usize) -> usize {
+    if tab_width == 0 {
+        0
+    } else {
+        tab_width - col % tab_width
     }
+}
 
-    let mut current_row = 0;
-    for (idx, &byte) in code.iter().enumerate() {
-        if byte == b'\n' {
-            current_row += 1;
-            if current_row == target_row {
-                return idx + 1;
+/// Render `text` for display, starting at display column `col`, and
+/// return the display column reached afterwards.
+///
+/// Tabs are expanded to the next multiple of `tab_width` and
+/// characters take up their Unicode display width. Bytes not forming
+/// valid UTF-8 are rendered as `\xNN` escape sequences, making it
+/// apparent where exactly the code is malformed.
+///
+/// If `rendered` is `None`, only the display column is calculated.
+fn render(
+    text: &[u8],
+    mut col: usize,
+    tab_width: usize,
+    mut rendered: Option<&mut String>,
+) -> usize {
+    for chunk in text.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if c == '\t' {
+                let advance = tab_advance(col, tab_width);
+                if let Some(rendered) = &mut rendered {
+                    let () = rendered.extend((0..advance).map(|_| ' '));
+                }
+                col += advance;
+            } else {
+                if let Some(rendered) = &mut rendered {
+                    let () = rendered.push(c);
+                }
+                col += c.width().unwrap_or(0);
             }
         }
-    }
 
-    // If we didn't find enough newlines, return the start of the code
-    0
+        for byte in chunk.invalid() {
+            let escaped = format!("\\x{byte:02X}");
+            col += escaped.len();
+            if let Some(rendered) = &mut rendered {
+                let () = rendered.push_str(&escaped);
+            }
+        }
+    }
+    col
 }
 
-/// Find the total number of lines in the code
-fn count_lines(code: &[u8]) -> usize {
-    if code.is_empty() {
-        return 0;
-    }
-    code.iter().filter(|&&b| b == b'\n').count() + 1
+/// Calculate the display column reached after rendering `text`,
+/// starting at display column `col`.
+fn advance_col(col: usize, text: &[u8], tab_width: usize) -> usize {
+    render(text, col, tab_width, None)
 }
 
-/// Find context lines before the error line
-fn find_context_lines_before(code: &[u8], start_row: usize, count: usize) -> Vec<(usize, usize)> {
-    if start_row == 0 || count == 0 {
-        return Vec::new();
-    }
+/// Render a line of code for display.
+pub(crate) fn render_line(line: &[u8], tab_width: usize) -> String {
+    let mut rendered = String::with_capacity(line.len());
+    let _col = render(line, 0, tab_width, Some(&mut rendered));
+    rendered
+}
 
-    let mut context_lines = Vec::new();
-    let start_search = start_row.saturating_sub(count);
+/// Calculate the display indentation and width of the carets
+/// underlining the byte columns `start_col` to `end_col` of `line`.
+pub(crate) fn caret_span(
+    line: &[u8],
+    start_col: usize,
+    end_col: usize,
+    tab_width: usize,
+) -> (usize, usize) {
+    let end_col = end_col.min(line.len());
+    let start_col = start_col.min(end_col);
+    let indent = advance_col(0, &line[..start_col], tab_width);
+    let width = advance_col(indent, &line[start_col..end_col], tab_width) - indent;
+    (indent, width)
+}
 
-    for row in start_search..start_row {
-        let line_start = find_line_start_by_row(code, row);
-        context_lines.push((row, line_start));
+/// Retrieve the style used for highlighting things of `severity`.
+fn severity_style(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Note => style::NOTE,
+        Severity::Warning => style::WARNING,
+        Severity::Error => style::ERROR,
     }
+}
 
-    context_lines
+/// Find context lines before the error line
+fn find_context_lines_before(index: &LineIndex, start_row: usize, count: usize) -> Vec<usize> {
+    let start_search = start_row.saturating_sub(count);
+    (start_search..start_row)
+        .filter(|row| *row < index.line_count())
+        .collect()
 }
 
 /// Find context lines after the error line (including empty lines)
-fn find_context_lines_after(code: &[u8], end_row: usize, count: usize) -> Vec<(usize, usize)> {
-    let total_lines = count_lines(code);
-    if end_row + 1 >= total_lines || count == 0 {
-        return Vec::new();
-    }
-
-    let mut context_lines = Vec::new();
-    let end_search = (end_row + 1 + count).min(total_lines);
-
-    for row in (end_row + 1)..end_search {
-        let line_start = find_line_start_by_row(code, row);
-        context_lines.push((row, line_start));
-    }
+fn find_context_lines_after(index: &LineIndex, end_row: usize, count: usize) -> Vec<usize> {
+    let end_search = (end_row + 1 + count).min(index.line_count());
+    ((end_row + 1)..end_search).collect()
+}
 
-    context_lines
+/// Determine the rows of the code snippet for `match`, i.e., the rows
+/// covered by the match itself as well as the context lines configured
+/// in `opts`.
+pub(crate) fn snippet_rows(
+    r#match: &LintMatch,
+    index: &LineIndex,
+    opts: &Opts,
+) -> ops::Range<usize> {
+    let start_row = r#match.range.start_point.row;
+    let end_row = r#match.range.end_point.row;
+    let before = find_context_lines_before(index, start_row, opts.lines_before());
+    let after = find_context_lines_after(index, end_row, opts.lines_after());
+    let first = before.first().copied().unwrap_or(start_row);
+    let last = after.last().copied().unwrap_or(end_row);
+    first..last + 1
 }
 
 /// Display context lines to the writer.
+///
+/// Row numbers are padded to `width` and lines are preceded by
+/// `margin`.
 fn display_context_lines(
-    context_lines: &[(usize, usize)],
+    context_rows: &[usize],
     code: &[u8],
+    index: &LineIndex,
+    width: usize,
+    margin: &str,
     writer: &mut dyn io::Write,
+    opts: &Opts,
 ) -> Result<()> {
-    for (context_row, context_byte) in context_lines {
-        let mut lines = Lines::new(code, *context_byte);
-        if let Some(line) = lines.next() {
-            let lprefix = format!("{context_row} | ");
-            writeln!(writer, "{lprefix}{}", String::from_utf8_lossy(line))?;
+    for context_row in context_rows {
+        if let Some(line) = index.line(code, *context_row) {
+            let lprefix = format!("{context_row:<width$} | ");
+            let lprefix = opts.paint(style::GUTTER, &lprefix);
+            let line = render_line(line, usize::from(opts.tab_width));
+            writeln!(writer, "{lprefix}{margin}{line}")?;
         }
     }
     Ok(())
 }
 
+/// Display the lines covered by the multi-line span of `match`, along
+/// with markers for its start and end.
+///
+/// `lines` is expected to start at the first line of the span. If the
+/// span covers more than [`MAX_SPAN_LINES`] lines, lines in its middle
+/// are elided.
+fn display_multiline_span(
+    r#match: &LintMatch,
+    lines: &mut Lines<'_>,
+    width: usize,
+    writer: &mut dyn io::Write,
+    opts: &Opts,
+) -> Result<()> {
+    let highlight = severity_style(r#match.severity);
+    let range = &r#match.range;
+    let start_row = range.start_point.row;
+    let end_row = range.end_point.row;
+    let tab_width = usize::from(opts.tab_width);
+    let is_elided = end_row - start_row + 1 > MAX_SPAN_LINES;
+
+    let prefix = format!("{:width$} | ", "");
+    let prefix = opts.paint(style::GUTTER, &prefix);
+
+    for (row, line) in (start_row..=end_row).zip(lines) {
+        if is_elided && row > start_row + SPAN_HEAD_LINES && row + SPAN_TAIL_LINES < end_row {
+            if row == start_row + SPAN_HEAD_LINES + 1 {
+                let lprefix = format!("{ELISION:<width$} | ");
+                let lprefix = opts.paint(style::GUTTER, &lprefix);
+                writeln!(writer, "{lprefix}{}", opts.paint(highlight, "|"))?;
+            }
+            continue
+        }
+
+        let lprefix = format!("{row:<width$} | ");
+        let lprefix = opts.paint(style::GUTTER, &lprefix);
+        let rendered = render_line(line, tab_width);
+
+        if row == start_row {
+            // NB: Columns are byte based. We need to convert them to
+            //     display columns for markers to line up with the
+            //     rendered code.
+            let start_col = range.start_point.col.min(line.len());
+            let is_leading = line[..start_col].iter().all(u8::is_ascii_whitespace);
+            if is_leading {
+                // If the span starts with the first non-whitespace
+                // character of the line, we just mark the line.
+                writeln!(writer, "{lprefix}{}{rendered}", opts.paint(highlight, "/ "))?;
+            } else {
+                let indent = advance_col(0, &line[..start_col], tab_width);
+                writeln!(writer, "{lprefix}  {rendered}")?;
+                let marker = format!(" {:_<width$}^", "", width = indent + 1);
+                writeln!(writer, "{prefix}{}", opts.paint(highlight, &marker))?;
+            }
+        } else {
+            writeln!(writer, "{lprefix}{}{rendered}", opts.paint(highlight, "| "))?;
+        }
+
+        if row == end_row {
+            let end_col = range.end_point.col.min(line.len());
+            let indent = advance_col(0, &line[..end_col], tab_width);
+            let marker = format!("|{:_<indent$}^", "");
+            writeln!(writer, "{prefix}{}", opts.paint(highlight, &marker))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the header of the report for `match`, consisting of the
+/// severity, lint name, message, and location.
+fn write_header(
+    r#match: &LintMatch,
+    path: &Path,
+    writer: &mut dyn io::Write,
+    opts: &Opts,
+) -> Result<()> {
+    let LintMatch {
+        lint_name,
+        message,
+        severity,
+        range,
+        fix: _,
+    } = r#match;
+
+    writeln!(
+        writer,
+        "{}: {} {message}",
+        opts.paint(severity_style(*severity), severity.as_str()),
+        opts.paint(style::BOLD, &format!("[{lint_name}]"))
+    )?;
+    writeln!(
+        writer,
+        "  {} {}:{}:{}",
+        opts.paint(style::GUTTER, "-->"),
+        path.display(),
+        range.start_point.row,
+        range.start_point.col,
+    )?;
+    Ok(())
+}
+
 /// Report a lint match in terminal style.
 ///
 /// - `match` is the match to create a report for
@@ -120,6 +351,11 @@ fn display_context_lines(
 /// - `writer` is a reference to a [`io::Write`] to which to write the
 ///   report
 ///
+/// Locations, both in the `-->` header and in the line number gutter,
+/// are reported as zero-based rows and byte columns, just like the
+/// [`Point`][crate::Point] objects they are derived from. Most other
+/// reporters use 1-based lines and columns instead.
+///
 /// # Example
 /// ```text
 /// warning: [probe-read] bpf_probe_read() is deprecated and replaced by
@@ -150,6 +386,12 @@ pub fn report_terminal(
 ///   report
 /// - `opts` specifies the reporting options including context lines
 ///
+/// This function indexes the lines of `code` on every invocation. When
+/// reporting multiple matches in the same file, use
+/// [`report_terminal_with_index`] with a shared [`LineIndex`] (or a
+/// [`Reporter`][crate::Reporter] operating on a
+/// [`SourceFile`][crate::SourceFile]) instead.
+///
 /// # Example
 /// ```text
 /// warning: [probe-read] bpf_probe_read() is deprecated and replaced by
@@ -170,80 +412,271 @@ pub fn report_terminal_opts(
     path: &Path,
     writer: &mut dyn io::Write,
     opts: &Opts,
+) -> Result<()> {
+    let index = LineIndex::new(code);
+    report_terminal_with_index(r#match, code, &index, path, writer, opts)
+}
+
+/// Report a lint match in terminal style, using a precomputed
+/// [`LineIndex`] of the code.
+///
+/// This function behaves like [`report_terminal_opts`], but it does
+/// not have to scan `code` for line boundaries. When reporting
+/// multiple matches in the same file, the index should be created once
+/// and shared among all of them.
+///
+/// `index` has to be the index of `code`.
+pub fn report_terminal_with_index(
+    r#match: &LintMatch,
+    code: &[u8],
+    index: &LineIndex,
+    path: &Path,
+    writer: &mut dyn io::Write,
+    opts: &Opts,
 ) -> Result<()> {
     let LintMatch {
-        lint_name,
-        message,
+        lint_name: _,
+        message: _,
+        severity,
         range,
+        fix: _,
     } = r#match;
 
-    writeln!(writer, "warning: [{lint_name}] {message}")?;
+    let highlight = severity_style(*severity);
+    let () = write_header(r#match, path, writer, opts)?;
     let start_row = range.start_point.row;
     let end_row = range.end_point.row;
     let start_col = range.start_point.col;
     let end_col = range.end_point.col;
-    writeln!(writer, "  --> {}:{start_row}:{start_col}", path.display())?;
 
     if range.bytes.is_empty() {
         return Ok(());
     }
 
     // Find context lines
-    let context_lines_before = find_context_lines_before(code, start_row, opts.lines_before());
-    let context_lines_after = find_context_lines_after(code, end_row, opts.lines_after());
+    let context_lines_before = find_context_lines_before(index, start_row, opts.lines_before());
+    let context_lines_after = find_context_lines_after(index, end_row, opts.lines_after());
+
+    let is_multiline = start_row != end_row;
+    let is_elided = is_multiline && end_row - start_row + 1 > MAX_SPAN_LINES;
 
     // Calculate the maximum row number for consistent indentation
-    let max_row = context_lines_after
-        .last()
-        .map(|(row, _)| *row)
-        .unwrap_or(end_row);
-    let prefix = format!("{:width$} | ", "", width = max_row.to_string().len());
+    let max_row = context_lines_after.last().copied().unwrap_or(end_row);
+    let mut width = max_row.to_string().len();
+    if is_elided {
+        width = width.max(ELISION.len());
+    }
+    let prefix = format!("{:width$} | ", "");
+    let prefix = opts.paint(style::GUTTER, &prefix);
     writeln!(writer, "{prefix}")?;
 
+    // Lines of multi-line spans are preceded by a margin holding the
+    // span's markers, which context lines have to account for.
+    let margin = if is_multiline { "  " } else { "" };
+
     // Show context lines before (if any)
-    display_context_lines(&context_lines_before, code, writer)?;
+    display_context_lines(
+        &context_lines_before,
+        code,
+        index,
+        width,
+        margin,
+        writer,
+        opts,
+    )?;
 
     // Show the error lines
-    let mut lines = Lines::new(code, range.bytes.start);
-    let is_multiline = start_row != end_row;
-
-    for (idx, row) in (start_row..=end_row).enumerate() {
-        let lprefix = format!("{row} | ");
-        if let Some(line) = lines.next() {
-            let mut c = "";
-            if is_multiline {
-                c = if idx == 0 { " / " } else { " | " };
-            }
+    // SANITY: The row of any offset is a valid row.
+    let mut lines = index
+        .lines(code, index.row(range.bytes.start))
+        .expect("Expected error line");
+    let tab_width = usize::from(opts.tab_width);
 
-            writeln!(writer, "{lprefix}{c}{}", String::from_utf8_lossy(line))?;
-        } else if idx == 0 {
-            // SANITY: It would be a tree-sitter bug IF the range does not
-            //         map to a valid code location.
-            panic!("Expected error line");
-        }
-    }
-
-    // Show the appropriate underline
     if is_multiline {
-        writeln!(writer, "{prefix} |{:_<width$}^", "", width = end_col)?;
+        let () = display_multiline_span(r#match, &mut lines, width, writer, opts)?;
     } else {
+        // SANITY: An iterator created for a valid row yields at least
+        //         one line.
+        let line = lines.next().expect("Expected error line");
+        let lprefix = format!("{start_row:<width$} | ");
+        let lprefix = opts.paint(style::GUTTER, &lprefix);
+        writeln!(writer, "{lprefix}{}", render_line(line, tab_width))?;
+
+        // NB: Columns are byte based. We need to convert them to display
+        //     columns for the underline to line up with the rendered code.
+        let (indent, width) = caret_span(line, start_col, end_col, tab_width);
+        let underline = format!("{:^<width$}", "");
         writeln!(
             writer,
-            "{prefix}{:indent$}{:^<width$}",
+            "{prefix}{:indent$}{}",
             "",
-            "",
-            indent = start_col,
-            width = end_col.saturating_sub(start_col)
+            opts.paint(highlight, &underline),
         )?;
     }
 
     // Show context lines after (if any)
-    display_context_lines(&context_lines_after, code, writer)?;
+    display_context_lines(
+        &context_lines_after,
+        code,
+        index,
+        width,
+        margin,
+        writer,
+        opts,
+    )?;
 
     writeln!(writer, "{prefix}")?;
     Ok(())
 }
 
+/// Check whether `match` can be reported as part of a group.
+///
+/// Only matches on a single line are grouped.
+fn is_groupable(r#match: &LintMatch) -> bool {
+    let range = &r#match.range;
+    !range.bytes.is_empty() && range.start_point.row == range.end_point.row
+}
+
+/// Report a group of single line `matches`, in order of ascending
+/// rows, as part of a single code snippet.
+fn report_group(
+    matches: &[&LintMatch],
+    code: &[u8],
+    index: &LineIndex,
+    path: &Path,
+    writer: &mut dyn io::Write,
+    opts: &Opts,
+) -> Result<()> {
+    for m in matches {
+        let () = write_header(m, path, writer, opts)?;
+    }
+
+    // SANITY: Groups are never empty.
+    let first_row = matches.first().unwrap().range.start_point.row;
+    let last_row = matches.iter().map(|m| m.range.end_point.row).max().unwrap();
+    let first_row = first_row.saturating_sub(opts.lines_before());
+    let last_row = (last_row + opts.lines_after()).min(index.line_count() - 1);
+
+    let width = last_row.to_string().len();
+    let prefix = format!("{:width$} | ", "");
+    let prefix = opts.paint(style::GUTTER, &prefix);
+    writeln!(writer, "{prefix}")?;
+
+    let tab_width = usize::from(opts.tab_width);
+    // SANITY: `first_row` is a valid row, as matches reference valid
+    //         locations.
+    let lines = index.lines(code, first_row).unwrap();
+    for (row, line) in (first_row..=last_row).zip(lines) {
+        let lprefix = format!("{row:<width$} | ");
+        let lprefix = opts.paint(style::GUTTER, &lprefix);
+        writeln!(writer, "{lprefix}{}", render_line(line, tab_width))?;
+
+        for m in matches.iter().filter(|m| m.range.start_point.row == row) {
+            let (indent, width) = caret_span(
+                line,
+                m.range.start_point.col,
+                m.range.end_point.col,
+                tab_width,
+            );
+            let underline = format!("{:^<width$} [{}]", "", m.lint_name);
+            writeln!(
+                writer,
+                "{prefix}{:indent$}{}",
+                "",
+                opts.paint(severity_style(m.severity), &underline),
+            )?;
+        }
+    }
+
+    writeln!(writer, "{prefix}")?;
+    Ok(())
+}
+
+/// Report all lint `matches` found in a file in terminal style.
+///
+/// - `matches` are the matches to create a report for, typically
+///   ordered by location
+/// - `code` is the source code in question, as passed to
+///   [`lint`][crate::lint()]
+/// - `index` is the [`LineIndex`] of `code`
+/// - `path` should be the path to the file to which `code` corresponds
+///   and is used to enhance the generated report
+/// - `writer` is a reference to a [`io::Write`] to which to write the
+///   report
+/// - `opts` specifies the reporting options
+///
+/// If [`Opts::group_matches`] is set, consecutive single line matches
+/// whose context windows overlap or touch are reported as part of a
+/// single code snippet, with each match underlined separately.
+///
+/// # Example
+/// ```text
+/// warning: [probe-read] bpf_probe_read() is deprecated
+///   --> example.bpf.c:2:4
+/// warning: [probe-read] bpf_probe_read() is deprecated
+///   --> example.bpf.c:4:4
+///   |
+/// 1 | {
+/// 2 |     bpf_probe_read(dst, sizeof(*dst), src);
+///   |     ^^^^^^^^^^^^^^ [probe-read]
+/// 3 |     dst++;
+/// 4 |     bpf_probe_read(dst, sizeof(*dst), src);
+///   |     ^^^^^^^^^^^^^^ [probe-read]
+/// 5 | }
+///   |
+/// ```
+pub fn report_terminal_matches(
+    matches: &[LintMatch],
+    code: &[u8],
+    index: &LineIndex,
+    path: &Path,
+    writer: &mut dyn io::Write,
+    opts: &Opts,
+) -> Result<()> {
+    let mut group = Vec::<&LintMatch>::new();
+    // The last row covered by the context window of the current group.
+    let mut group_end = 0;
+
+    for m in matches {
+        if opts.group_matches && is_groupable(m) {
+            let start_row = m.range.start_point.row;
+            let joins = group.last().is_some_and(|last| {
+                start_row >= last.range.start_point.row
+                    && start_row.saturating_sub(opts.lines_before()) <= group_end + 1
+            });
+
+            if !joins {
+                let () = flush_group(&mut group, code, index, path, writer, opts)?;
+                group_end = 0;
+            }
+            group_end = group_end.max(m.range.end_point.row + opts.lines_after());
+            let () = group.push(m);
+        } else {
+            let () = flush_group(&mut group, code, index, path, writer, opts)?;
+            let () = report_terminal_with_index(m, code, index, path, writer, opts)?;
+        }
+    }
+    flush_group(&mut group, code, index, path, writer, opts)
+}
+
+/// Report and clear the current `group` of matches.
+fn flush_group(
+    group: &mut Vec<&LintMatch>,
+    code: &[u8],
+    index: &LineIndex,
+    path: &Path,
+    writer: &mut dyn io::Write,
+    opts: &Opts,
+) -> Result<()> {
+    match group.as_slice() {
+        [] => (),
+        [m] => report_terminal_with_index(m, code, index, path, writer, opts)?,
+        matches => report_group(matches, code, index, path, writer, opts)?,
+    }
+    let () = group.clear();
+    Ok(())
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -255,6 +688,7 @@ mod tests {
 
     use crate::Point;
     use crate::Range;
+    use crate::Severity;
 
 
     /// Tests that a match with an empty range includes no code snippet.
@@ -267,11 +701,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "bogus-file-extension".to_string(),
             message: "by convention BPF C code should use the file extension '.bpf.c'".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 0..0,
                 start_point: Point::default(),
                 end_point: Point::default(),
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () =
@@ -301,11 +737,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "probe-read".to_string(),
             message: "bpf_probe_read() is deprecated".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 68..140,
                 start_point: Point { row: 2, col: 4 },
                 end_point: Point { row: 5, col: 17 },
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () = report_terminal(&m, code.as_bytes(), Path::new("<stdin>"), &mut report).unwrap();
@@ -315,11 +753,11 @@ mod tests {
           warning: [probe-read] bpf_probe_read() is deprecated
             --> <stdin>:2:4
             | 
-          2 |  /     bpf_probe_read(
-          3 |  |       event.comm,
-          4 |  |       TASK_COMM_LEN,
-          5 |  |       prev->comm);
-            |  |_________________^
+          2 | /     bpf_probe_read(
+          3 | |       event.comm,
+          4 | |       TASK_COMM_LEN,
+          5 | |       prev->comm);
+            | |_________________^
             | 
         "# };
         assert_eq!(report, expected);
@@ -342,11 +780,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "probe-read".to_string(),
             message: "bpf_probe_read() is deprecated".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 160..174,
                 start_point: Point { row: 6, col: 4 },
                 end_point: Point { row: 6, col: 18 },
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () = report_terminal(&m, code.as_bytes(), Path::new("<stdin>"), &mut report).unwrap();
@@ -377,11 +817,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "unstable-attach-point".to_string(),
             message: "kprobe/kretprobe/fentry/fexit are unstable".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 4..17,
                 start_point: Point { row: 0, col: 4 },
                 end_point: Point { row: 0, col: 17 },
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () = report_terminal(&m, code.as_bytes(), Path::new("<stdin>"), &mut report).unwrap();
@@ -416,11 +858,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "probe-read".to_string(),
             message: "bpf_probe_read() is deprecated".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 160..174,
                 start_point: Point { row: 5, col: 4 },
                 end_point: Point { row: 5, col: 18 },
             },
+            fix: None,
         };
 
         let mut report_old = Vec::new();
@@ -457,11 +901,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "probe-read".to_string(),
             message: "bpf_probe_read() is deprecated".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 160..174,
                 start_point: Point { row: 5, col: 4 },
                 end_point: Point { row: 5, col: 18 },
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () = report_terminal_opts(
@@ -471,6 +917,7 @@ mod tests {
             &mut report,
             &Opts {
                 extra_lines: Some((2, 1)),
+                ..Default::default()
             },
         )
         .unwrap();
@@ -507,11 +954,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "probe-read".to_string(),
             message: "bpf_probe_read() is deprecated".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 68..140,
                 start_point: Point { row: 2, col: 4 },
                 end_point: Point { row: 5, col: 17 },
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () = report_terminal_opts(
@@ -521,6 +970,7 @@ mod tests {
             &mut report,
             &Opts {
                 extra_lines: Some((1, 1)),
+                ..Default::default()
             },
         )
         .unwrap();
@@ -530,13 +980,13 @@ mod tests {
             warning: [probe-read] bpf_probe_read() is deprecated
               --> <stdin>:2:4
               | 
-            1 | int handle__sched_switch(u64 *ctx) {
-            2 |  /     bpf_probe_read(
-            3 |  |       event.comm,
-            4 |  |       TASK_COMM_LEN,
-            5 |  |       prev->comm);
-              |  |_________________^
-            6 |     return 0;
+            1 |   int handle__sched_switch(u64 *ctx) {
+            2 | /     bpf_probe_read(
+            3 | |       event.comm,
+            4 | |       TASK_COMM_LEN,
+            5 | |       prev->comm);
+              | |_________________^
+            6 |       return 0;
               | 
         "# };
         assert_eq!(report, expected);
@@ -555,11 +1005,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "unstable-attach-point".to_string(),
             message: "kprobe/kretprobe/fentry/fexit are unstable".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 4..17,
                 start_point: Point { row: 0, col: 4 },
                 end_point: Point { row: 0, col: 17 },
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () = report_terminal_opts(
@@ -569,6 +1021,7 @@ mod tests {
             &mut report,
             &Opts {
                 extra_lines: Some((5, 2)),
+                ..Default::default()
             },
         )
         .unwrap();
@@ -601,11 +1054,13 @@ mod tests {
         let m = LintMatch {
             lint_name: "probe-read".to_string(),
             message: "bpf_probe_read() is deprecated".to_string(),
+            severity: Severity::Warning,
             range: Range {
                 bytes: 68..82,
                 start_point: Point { row: 3, col: 4 },
                 end_point: Point { row: 3, col: 18 },
             },
+            fix: None,
         };
         let mut report = Vec::new();
         let () = report_terminal_opts(
@@ -615,6 +1070,7 @@ mod tests {
             &mut report,
             &Opts {
                 extra_lines: Some((1, 5)),
+                ..Default::default()
             },
         )
         .unwrap();
@@ -644,64 +1100,567 @@ mod tests {
 
         let extra_opts = Opts {
             extra_lines: Some((3, 5)),
+            ..Default::default()
         };
         assert_eq!(extra_opts.lines_before(), 3);
         assert_eq!(extra_opts.lines_after(), 5);
     }
 
     /// Test helper functions for finding context lines.
-    #[test]
-    fn line_start_by_row() {
-        let code = b"line 0\nline 1\nline 2\n";
-        assert_eq!(find_line_start_by_row(code, 0), 0);
-        assert_eq!(find_line_start_by_row(code, 1), 7);
-        assert_eq!(find_line_start_by_row(code, 2), 14);
-        assert_eq!(find_line_start_by_row(code, 10), 0); // Beyond available lines
-    }
-
-    #[test]
-    fn line_counting() {
-        assert_eq!(count_lines(b""), 0);
-        assert_eq!(count_lines(b"single line"), 1);
-        assert_eq!(count_lines(b"line 1\nline 2"), 2);
-        assert_eq!(count_lines(b"line 1\nline 2\n"), 3);
-    }
-
     #[test]
     fn context_lines_before() {
         let code = b"line 0\nline 1\nline 2\nline 3\n";
+        let index = LineIndex::new(code);
 
         // No context requested
-        assert_eq!(find_context_lines_before(code, 2, 0), vec![]);
+        assert_eq!(find_context_lines_before(&index, 2, 0), vec![]);
 
         // Context from row 0 (should return empty)
-        assert_eq!(find_context_lines_before(code, 0, 2), vec![]);
+        assert_eq!(find_context_lines_before(&index, 0, 2), vec![]);
 
         // Normal context
-        let result = find_context_lines_before(code, 3, 2);
-        assert_eq!(result, vec![(1, 7), (2, 14)]);
+        let result = find_context_lines_before(&index, 3, 2);
+        assert_eq!(result, vec![1, 2]);
 
         // More context than available
-        let result = find_context_lines_before(code, 2, 5);
-        assert_eq!(result, vec![(0, 0), (1, 7)]);
+        let result = find_context_lines_before(&index, 2, 5);
+        assert_eq!(result, vec![0, 1]);
     }
 
     #[test]
     fn context_lines_after() {
         let code = b"line 0\nline 1\nline 2\nline 3\n";
+        let index = LineIndex::new(code);
 
         // No context requested
-        assert_eq!(find_context_lines_after(code, 1, 0), vec![]);
+        assert_eq!(find_context_lines_after(&index, 1, 0), vec![]);
 
         // Context beyond available lines - asking for 2 lines after row 3, but only row 4 (empty) exists
-        assert_eq!(find_context_lines_after(code, 3, 2), vec![(4, 28)]);
+        assert_eq!(find_context_lines_after(&index, 3, 2), vec![4]);
 
         // Normal context
-        let result = find_context_lines_after(code, 0, 2);
-        assert_eq!(result, vec![(1, 7), (2, 14)]);
+        let result = find_context_lines_after(&index, 0, 2);
+        assert_eq!(result, vec![1, 2]);
 
         // More context than available - asking for 5 lines after row 2, but only rows 3 and 4 exist
-        let result = find_context_lines_after(code, 2, 5);
-        assert_eq!(result, vec![(3, 21), (4, 28)]);
+        let result = find_context_lines_after(&index, 2, 5);
+        assert_eq!(result, vec![3, 4]);
+    }
+
+    /// Check that colored reports contain the expected escape
+    /// sequences, while uncolored ones contain none.
+    #[test]
+    fn colored_reporting() {
+        let code = indoc! { r#"
+          int main() {
+              foo();
+          }
+        "# };
+        let m = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo is bad".to_string(),
+            severity: Severity::Error,
+            range: Range {
+                bytes: 17..20,
+                start_point: Point { row: 1, col: 4 },
+                end_point: Point { row: 1, col: 7 },
+            },
+            fix: None,
+        };
+        let opts = Opts {
+            extra_lines: Some((1, 0)),
+            color: true,
+            ..Default::default()
+        };
+
+        let mut report = Vec::new();
+        let () = report_terminal_opts(&m, code.as_bytes(), Path::new("foo.c"), &mut report, &opts)
+            .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let expected = "\
+\x1b[1;31merror\x1b[0m: \x1b[1m[foo]\x1b[0m foo is bad
+  \x1b[1;34m-->\x1b[0m foo.c:1:4
+\x1b[1;34m  | \x1b[0m
+\x1b[1;34m0 | \x1b[0mint main() {
+\x1b[1;34m1 | \x1b[0m    foo();
+\x1b[1;34m  | \x1b[0m    \x1b[1;31m^^^\x1b[0m
+\x1b[1;34m  | \x1b[0m
+";
+        assert_eq!(report, expected);
+
+        let opts = Opts {
+            color: false,
+            ..opts
+        };
+        let mut report = Vec::new();
+        let () = report_terminal_opts(&m, code.as_bytes(), Path::new("foo.c"), &mut report, &opts)
+            .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(!report.contains('\x1b'), "{report}");
+    }
+
+    /// Check that carets are aligned with the code when it contains
+    /// tabs and wide characters.
+    #[test]
+    fn tab_and_wide_char_alignment() {
+        let code = "int main() {\n\tfoo(\"\u{4f60}\u{597d}\", bar);\n}\n";
+        let start = code.find("bar").unwrap();
+        let line_start = code.find('\t').unwrap();
+        let m = LintMatch {
+            lint_name: "bar".to_string(),
+            message: "bar is bad".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: start..start + 3,
+                start_point: Point {
+                    row: 1,
+                    col: start - line_start,
+                },
+                end_point: Point {
+                    row: 1,
+                    col: start - line_start + 3,
+                },
+            },
+            fix: None,
+        };
+
+        let mut report = Vec::new();
+        let () = report_terminal(&m, code.as_bytes(), Path::new("<stdin>"), &mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let expected = "\
+warning: [bar] bar is bad
+  --> <stdin>:1:15
+  | 
+1 |     foo(\"\u{4f60}\u{597d}\", bar);
+  |                 ^^^
+  | 
+";
+        assert_eq!(report, expected);
+
+        let opts = Opts {
+            tab_width: 8,
+            ..Default::default()
+        };
+        let mut report = Vec::new();
+        let () = report_terminal_opts(
+            &m,
+            code.as_bytes(),
+            Path::new("<stdin>"),
+            &mut report,
+            &opts,
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let expected = "\
+warning: [bar] bar is bad
+  --> <stdin>:1:15
+  | 
+1 |         foo(\"\u{4f60}\u{597d}\", bar);
+  |                     ^^^
+  | 
+";
+        assert_eq!(report, expected);
+    }
+
+    /// Check that CRLF line endings do not show up in reports.
+    #[test]
+    fn crlf_reporting() {
+        let code = b"int main() {\r\n    foo();\r\n}\r\n";
+        let m = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo is bad".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 18..21,
+                start_point: Point { row: 1, col: 4 },
+                end_point: Point { row: 1, col: 7 },
+            },
+            fix: None,
+        };
+        let opts = Opts {
+            extra_lines: Some((1, 1)),
+            ..Default::default()
+        };
+
+        let mut report = Vec::new();
+        let () = report_terminal_opts(&m, code, Path::new("<stdin>"), &mut report, &opts).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let expected = indoc! { r#"
+          warning: [foo] foo is bad
+            --> <stdin>:1:4
+            | 
+          0 | int main() {
+          1 |     foo();
+            |     ^^^
+          2 | }
+            | 
+        "# };
+        assert_eq!(report, expected);
+    }
+
+    /// Check that invalid UTF-8 is reported as escape sequences, with
+    /// carets aligned accordingly.
+    #[test]
+    fn invalid_utf8_reporting() {
+        let code = b"char *s = \"\xff\xfe\"; foo();\n";
+        let m = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo is bad".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 16..19,
+                start_point: Point { row: 0, col: 16 },
+                end_point: Point { row: 0, col: 19 },
+            },
+            fix: None,
+        };
+
+        let mut report = Vec::new();
+        let () = report_terminal(&m, code, Path::new("<stdin>"), &mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let expected = indoc! { r#"
+          warning: [foo] foo is bad
+            --> <stdin>:0:16
+            | 
+          0 | char *s = "\xFF\xFE"; foo();
+            |                       ^^^
+            | 
+        "# };
+        assert_eq!(report, expected);
+    }
+
+    /// Check that the display column computation handles tabs, wide
+    /// characters, and a tab width of zero.
+    #[test]
+    fn display_column_advancing() {
+        assert_eq!(advance_col(0, b"abc", 4), 3);
+        assert_eq!(advance_col(0, b"\tx", 4), 5);
+        assert_eq!(advance_col(2, b"\tx", 4), 5);
+        assert_eq!(advance_col(0, b"a\tb\t", 8), 16);
+        assert_eq!(advance_col(0, b"\tx", 0), 1);
+        assert_eq!(advance_col(0, "\u{4f60}\u{597d}".as_bytes(), 4), 4);
+        assert_eq!(render_line(b"a\tb", 4), "a   b");
+        assert_eq!(render_line(b"\t\t", 0), "");
+        assert_eq!(advance_col(0, b"a\xffb", 4), 6);
+        assert_eq!(render_line(b"a\xffb", 4), "a\\xFFb");
+        // A truncated multi-byte sequence is escaped byte by byte.
+        assert_eq!(render_line(b"\xe4\xbd", 4), "\\xE4\\xBD");
+    }
+
+    /// Check that a multi-line span not starting at the beginning of a
+    /// line has its start column marked.
+    #[test]
+    fn multi_line_report_start_marker() {
+        let code = indoc! { r#"
+          int x = foo(
+            a,
+            b);
+        "# };
+
+        let m = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo is bad".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 8..27,
+                start_point: Point { row: 0, col: 8 },
+                end_point: Point { row: 2, col: 4 },
+            },
+            fix: None,
+        };
+        let mut report = Vec::new();
+        let () = report_terminal(&m, code.as_bytes(), Path::new("<stdin>"), &mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+
+        let expected = indoc! { r#"
+          warning: [foo] foo is bad
+            --> <stdin>:0:8
+            | 
+          0 |   int x = foo(
+            |  _________^
+          1 | |   a,
+          2 | |   b);
+            | |____^
+            | 
+        "# };
+        assert_eq!(report, expected);
+    }
+
+    /// Check that a multi-line span ending at the start of the empty
+    /// line following a trailing newline has its end marked.
+    #[test]
+    fn multi_line_report_end_of_file() {
+        let code = indoc! { r#"
+          foo(
+            a);
+        "# };
+
+        let m = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo is bad".to_string(),
+            severity: Severity::Warning,
+            range: Range {
+                bytes: 0..12,
+                start_point: Point { row: 0, col: 0 },
+                end_point: Point { row: 2, col: 0 },
+            },
+            fix: None,
+        };
+        let mut report = Vec::new();
+        let () = report_terminal(&m, code.as_bytes(), Path::new("<stdin>"), &mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+
+        let expected = indoc! { r#"
+          warning: [foo] foo is bad
+            --> <stdin>:0:0
+            | 
+          0 | / foo(
+          1 | |   a);
+          2 | | 
+            | |^
+            | 
+        "# };
+        assert_eq!(report, expected);
+    }
+
+    /// Check that the middle of large multi-line spans is elided.
+    #[test]
+    fn large_multi_line_report() {
+        let mut code = String::from("void foo(void)\n{\n");
+        for idx in 0..20 {
+            let () = code.push_str(&format!("    bar({idx});\n"));
+        }
+        let () = code.push_str("}\n");
+        let end = code.len() - 1;
+
+        let m = LintMatch {
+            lint_name: "foo".to_string(),
+            message: "foo is bad".to_string(),
+            severity: Severity::Error,
+            range: Range {
+                bytes: 0..end,
+                start_point: Point { row: 0, col: 0 },
+                end_point: Point { row: 22, col: 1 },
+            },
+            fix: None,
+        };
+        let mut report = Vec::new();
+        let () = report_terminal_opts(
+            &m,
+            code.as_bytes(),
+            Path::new("<stdin>"),
+            &mut report,
+            &Opts {
+                extra_lines: Some((0, 1)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+
+        let expected = indoc! { r#"
+          error: [foo] foo is bad
+            --> <stdin>:0:0
+              | 
+          0   | / void foo(void)
+          1   | | {
+          2   | |     bar(0);
+          3   | |     bar(1);
+          ... | |
+          20  | |     bar(18);
+          21  | |     bar(19);
+          22  | | }
+              | |_^
+          23  |   
+              | 
+        "# };
+        assert_eq!(report, expected);
+    }
+
+    /// Check that nearby matches are grouped into a single snippet when
+    /// requested.
+    #[test]
+    fn grouped_reporting() {
+        let code = indoc! { r#"
+          {
+              bpf_probe_read(dst, sizeof(*dst), src);
+              dst++;
+              bpf_probe_read(dst, sizeof(*dst), src);
+          }
+
+          {
+              bpf_probe_read(dst, sizeof(*dst), src);
+          }
+        "# };
+        let index = LineIndex::new(code.as_bytes());
+        let m = |row: usize| {
+            let start = index.line_start(row).unwrap();
+            LintMatch {
+                lint_name: "probe-read".to_string(),
+                message: "bpf_probe_read() is deprecated".to_string(),
+                severity: Severity::Warning,
+                range: Range {
+                    bytes: start + 4..start + 18,
+                    start_point: Point { row, col: 4 },
+                    end_point: Point { row, col: 18 },
+                },
+                fix: None,
+            }
+        };
+        let matches = [m(1), m(3), m(7)];
+        let opts = Opts {
+            extra_lines: Some((1, 1)),
+            group_matches: true,
+            ..Default::default()
+        };
+
+        let mut report = Vec::new();
+        let () = report_terminal_matches(
+            &matches,
+            code.as_bytes(),
+            &index,
+            Path::new("<stdin>"),
+            &mut report,
+            &opts,
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let expected = indoc! { r#"
+          warning: [probe-read] bpf_probe_read() is deprecated
+            --> <stdin>:1:4
+          warning: [probe-read] bpf_probe_read() is deprecated
+            --> <stdin>:3:4
+            | 
+          0 | {
+          1 |     bpf_probe_read(dst, sizeof(*dst), src);
+            |     ^^^^^^^^^^^^^^ [probe-read]
+          2 |     dst++;
+          3 |     bpf_probe_read(dst, sizeof(*dst), src);
+            |     ^^^^^^^^^^^^^^ [probe-read]
+          4 | }
+            | 
+          warning: [probe-read] bpf_probe_read() is deprecated
+            --> <stdin>:7:4
+            | 
+          6 | {
+          7 |     bpf_probe_read(dst, sizeof(*dst), src);
+            |     ^^^^^^^^^^^^^^
+          8 | }
+            | 
+        "# };
+        assert_eq!(report, expected);
+
+        // Without grouping, each match is reported separately.
+        let opts = Opts {
+            group_matches: false,
+            ..opts
+        };
+        let mut report = Vec::new();
+        let () = report_terminal_matches(
+            &matches,
+            code.as_bytes(),
+            &index,
+            Path::new("<stdin>"),
+            &mut report,
+            &opts,
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+
+        let mut expected = Vec::new();
+        for m in &matches {
+            let () = report_terminal_opts(
+                m,
+                code.as_bytes(),
+                Path::new("<stdin>"),
+                &mut expected,
+                &opts,
+            )
+            .unwrap();
+        }
+        let expected = String::from_utf8(expected).unwrap();
+        assert_eq!(report, expected);
+    }
+
+    /// Create a multi-thousand-line file and a set of matches spread
+    /// evenly throughout it.
+    #[cfg(feature = "nightly")]
+    fn large_file() -> (String, Vec<LintMatch>) {
+        let mut code = String::new();
+        let mut matches = Vec::new();
+        for row in 0..5000 {
+            let start = code.len();
+            let () = code.push_str("    bpf_probe_read(dst, sizeof(*dst), src);\n");
+            if row % 10 == 0 {
+                let () = matches.push(LintMatch {
+                    lint_name: "probe-read".to_string(),
+                    message: "bpf_probe_read() is deprecated".to_string(),
+                    severity: Severity::Warning,
+                    range: Range {
+                        bytes: start + 4..start + 18,
+                        start_point: Point { row, col: 4 },
+                        end_point: Point { row, col: 18 },
+                    },
+                    fix: None,
+                });
+            }
+        }
+        (code, matches)
+    }
+
+    /// Benchmark the reporting of many matches in a large file, creating
+    /// a line index for each match.
+    #[cfg(feature = "nightly")]
+    #[bench]
+    fn bench_report_per_match_index(b: &mut test::Bencher) {
+        let (code, matches) = large_file();
+        let opts = Opts {
+            extra_lines: Some((3, 3)),
+            ..Default::default()
+        };
+
+        let () = b.iter(|| {
+            let mut report = Vec::new();
+            for m in &matches {
+                let () = report_terminal_opts(
+                    m,
+                    code.as_bytes(),
+                    Path::new("<stdin>"),
+                    &mut report,
+                    &opts,
+                )
+                .unwrap();
+            }
+            test::black_box(report)
+        });
+    }
+
+    /// Benchmark the reporting of many matches in a large file, sharing
+    /// a single line index among all of them.
+    #[cfg(feature = "nightly")]
+    #[bench]
+    fn bench_report_shared_index(b: &mut test::Bencher) {
+        let (code, matches) = large_file();
+        let opts = Opts {
+            extra_lines: Some((3, 3)),
+            ..Default::default()
+        };
+
+        let () = b.iter(|| {
+            let mut report = Vec::new();
+            let index = LineIndex::new(code.as_bytes());
+            for m in &matches {
+                let () = report_terminal_with_index(
+                    m,
+                    code.as_bytes(),
+                    &index,
+                    Path::new("<stdin>"),
+                    &mut report,
+                    &opts,
+                )
+                .unwrap();
+            }
+            test::black_box(report)
+        });
     }
 }
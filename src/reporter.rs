@@ -0,0 +1,327 @@
+use std::io;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::LintMatch;
+use crate::lines::LineIndex;
+use crate::report::Opts;
+use crate::report::report_terminal_matches;
+use crate::report::report_terminal_with_index;
+
+
+/// A source file whose lint matches are being reported.
+#[derive(Debug)]
+pub struct SourceFile<'src> {
+    /// The path to the file.
+    path: &'src Path,
+    /// The source code contained in the file.
+    code: &'src [u8],
+    /// The line index of `code`.
+    index: LineIndex,
+}
+
+impl<'src> SourceFile<'src> {
+    /// Create a new [`SourceFile`] object for `code`, as contained in
+    /// the file at `path`.
+    pub fn new(path: &'src Path, code: &'src [u8]) -> Self {
+        Self {
+            path,
+            code,
+            index: LineIndex::new(code),
+        }
+    }
+
+    /// Retrieve the path to the file.
+    #[inline]
+    pub fn path(&self) -> &'src Path {
+        self.path
+    }
+
+    /// Retrieve the source code contained in the file.
+    #[inline]
+    pub fn code(&self) -> &'src [u8] {
+        self.code
+    }
+
+    /// Retrieve the [`LineIndex`] of the file's source code.
+    #[inline]
+    pub fn index(&self) -> &LineIndex {
+        &self.index
+    }
+}
+
+
+/// A consumer of lint matches, producing a report in some format.
+///
+/// Over the course of a run, a reporter is informed about the events
+/// in the following order:
+/// - [`Reporter::begin_run`], once
+/// - for each linted file:
+///   - [`Reporter::begin_file`]
+///   - [`Reporter::report_match`], for each of the file's matches
+///   - [`Reporter::end_file`]
+/// - [`Reporter::end_run`], once
+pub trait Reporter {
+    /// Begin a run, before any file is reported.
+    fn begin_run(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Begin reporting matches in `file`.
+    fn begin_file(&mut self, _file: &SourceFile<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Report the match `match` found in `file`.
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()>;
+
+    /// Finish reporting matches in `file`.
+    fn end_file(&mut self, _file: &SourceFile<'_>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Finish a run, after all files have been reported.
+    fn end_run(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Report all `matches` found in `file`, from beginning to end.
+    fn report_file(&mut self, file: &SourceFile<'_>, matches: &[LintMatch]) -> Result<()> {
+        let () = self.begin_file(file)?;
+        for m in matches {
+            let () = self.report_match(file, m)?;
+        }
+        self.end_file(file)
+    }
+}
+
+
+/// A [`Reporter`] producing terminal style reports.
+///
+/// Please refer to [`report_terminal_opts`][crate::report_terminal_opts]
+/// for an example of the produced output. As with
+/// [`report_terminal`][crate::report_terminal], locations are reported
+/// as zero-based rows and columns.
+#[derive(Debug)]
+pub struct TerminalReporter<W> {
+    /// The writer to which to write reports.
+    writer: W,
+    /// The reporting options.
+    opts: Opts,
+    /// Matches of the current file, buffered for grouping.
+    matches: Vec<LintMatch>,
+}
+
+impl<W> TerminalReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`TerminalReporter`] writing reports configured by
+    /// `opts` to `writer`.
+    pub fn new(writer: W, opts: Opts) -> Self {
+        Self {
+            writer,
+            opts,
+            matches: Vec::new(),
+        }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Reporter for TerminalReporter<W>
+where
+    W: io::Write,
+{
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        // Grouping needs to know about nearby matches, so we can only
+        // report once we have seen all matches of the file.
+        if self.opts.group_matches {
+            let () = self.matches.push(r#match.clone());
+            Ok(())
+        } else {
+            report_terminal_with_index(
+                r#match,
+                file.code(),
+                file.index(),
+                file.path(),
+                &mut self.writer,
+                &self.opts,
+            )
+        }
+    }
+
+    fn end_file(&mut self, file: &SourceFile<'_>) -> Result<()> {
+        let () = report_terminal_matches(
+            &self.matches,
+            file.code(),
+            file.index(),
+            file.path(),
+            &mut self.writer,
+            &self.opts,
+        )?;
+        let () = self.matches.clear();
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+    use crate::Severity;
+    use crate::report_terminal_opts;
+
+
+    /// A [`Reporter`] recording the events it is informed about.
+    #[derive(Debug, Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Reporter for Recorder {
+        fn begin_run(&mut self) -> Result<()> {
+            let () = self.events.push("begin-run".to_string());
+            Ok(())
+        }
+
+        fn begin_file(&mut self, file: &SourceFile<'_>) -> Result<()> {
+            let () = self
+                .events
+                .push(format!("begin-file {}", file.path().display()));
+            Ok(())
+        }
+
+        fn report_match(&mut self, _file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+            let () = self.events.push(format!("match {}", r#match.lint_name));
+            Ok(())
+        }
+
+        fn end_file(&mut self, file: &SourceFile<'_>) -> Result<()> {
+            let () = self
+                .events
+                .push(format!("end-file {}", file.path().display()));
+            Ok(())
+        }
+
+        fn end_run(&mut self) -> Result<()> {
+            let () = self.events.push("end-run".to_string());
+            Ok(())
+        }
+    }
+
+
+    fn matches(code: &str) -> Vec<LintMatch> {
+        let m = |lint_name: &str, row: usize| {
+            let start = code
+                .lines()
+                .take(row)
+                .map(|line| line.len() + 1)
+                .sum::<usize>();
+            LintMatch {
+                lint_name: lint_name.to_string(),
+                message: format!("{lint_name} is bad"),
+                severity: Severity::Warning,
+                range: Range {
+                    bytes: start..start + 3,
+                    start_point: Point { row, col: 0 },
+                    end_point: Point { row, col: 3 },
+                },
+                fix: None,
+            }
+        };
+        vec![m("foo", 0), m("bar", 1)]
+    }
+
+
+    /// Check that reporters are informed about events in the expected
+    /// order.
+    #[test]
+    fn event_ordering() {
+        let code = "foo();\nbar();\n";
+        let matches = matches(code);
+        let mut recorder = Recorder::default();
+
+        let () = recorder.begin_run().unwrap();
+        for path in ["a.bpf.c", "b.bpf.c"] {
+            let file = SourceFile::new(Path::new(path), code.as_bytes());
+            let () = recorder.report_file(&file, &matches).unwrap();
+        }
+        let () = recorder.end_run().unwrap();
+
+        let expected = [
+            "begin-run",
+            "begin-file a.bpf.c",
+            "match foo",
+            "match bar",
+            "end-file a.bpf.c",
+            "begin-file b.bpf.c",
+            "match foo",
+            "match bar",
+            "end-file b.bpf.c",
+            "end-run",
+        ];
+        assert_eq!(recorder.events, expected);
+    }
+
+    /// Check that the terminal reporter produces the same output as the
+    /// corresponding free-standing functions.
+    #[test]
+    fn terminal_reporting() {
+        let code = indoc! { r#"
+          foo();
+          bar();
+        "# };
+        let matches = matches(code);
+        let path = Path::new("foo.bpf.c");
+
+        for group_matches in [false, true] {
+            let opts = Opts {
+                group_matches,
+                ..Default::default()
+            };
+            let mut reporter = TerminalReporter::new(Vec::new(), opts.clone());
+            let file = SourceFile::new(path, code.as_bytes());
+            let () = reporter.begin_run().unwrap();
+            let () = reporter.report_file(&file, &matches).unwrap();
+            let () = reporter.end_run().unwrap();
+            let report = String::from_utf8(reporter.into_inner()).unwrap();
+
+            let mut expected = Vec::new();
+            if group_matches {
+                let () = report_terminal_matches(
+                    &matches,
+                    code.as_bytes(),
+                    file.index(),
+                    path,
+                    &mut expected,
+                    &opts,
+                )
+                .unwrap();
+            } else {
+                for m in &matches {
+                    let () = report_terminal_opts(m, code.as_bytes(), path, &mut expected, &opts)
+                        .unwrap();
+                }
+            }
+            let expected = String::from_utf8(expected).unwrap();
+            assert_eq!(report, expected);
+        }
+    }
+}
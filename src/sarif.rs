@@ -0,0 +1,263 @@
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::LintMatch;
+use crate::Severity;
+use crate::json::escape;
+use crate::reporter::Reporter;
+use crate::reporter::SourceFile;
+
+
+/// The URI of the JSON schema of the produced reports.
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// The SARIF version of the produced reports.
+const VERSION: &str = "2.1.0";
+
+
+/// Map `severity` to the corresponding SARIF level.
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Note => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+/// Convert the byte based column `col` on the line at `row` in `file`
+/// into a 1-based column, counted in UTF-16 code units.
+fn sarif_column(file: &SourceFile<'_>, row: usize, col: usize) -> usize {
+    let line = file.index().line(file.code(), row).unwrap_or_default();
+    let col = col.min(line.len());
+    String::from_utf8_lossy(&line[..col]).encode_utf16().count() + 1
+}
+
+/// Percent-encode `path` for use as a (relative) URI reference.
+fn sarif_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let mut uri = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'/') {
+            let () = uri.push(char::from(b));
+        } else {
+            // SANITY: Writing to a `String` never fails.
+            let () = write!(uri, "%{b:02X}").unwrap();
+        }
+    }
+    uri
+}
+
+
+/// A [`Reporter`] producing reports in the Static Analysis Results
+/// Interchange Format (SARIF), version 2.1.0.
+///
+/// All matches are reported as results of a single run. Lines are
+/// reported as 1-based numbers and columns as 1-based numbers of
+/// UTF-16 code units, with the end column being exclusive.
+///
+/// # Example
+/// ```json
+/// {
+///   "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+///   "version": "2.1.0",
+///   "runs": [
+///     {
+///       "tool": {"driver": {"name": "bpflint", "version": "0.1.2", "informationUri": "https://github.com/d-e-s-o/bpflint"}},
+///       "columnKind": "utf16CodeUnits",
+///       "results": [
+///         {"ruleId": "probe-read", "level": "warning", "message": {"text": "bpf_probe_read() is deprecated"}, "locations": [{"physicalLocation": {"artifactLocation": {"uri": "example.bpf.c"}, "region": {"startLine": 44, "startColumn": 5, "endLine": 44, "endColumn": 19}}}]}
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SarifReporter<W> {
+    /// The writer to which to write the report.
+    writer: W,
+    /// The number of results reported so far.
+    count: usize,
+}
+
+impl<W> SarifReporter<W>
+where
+    W: io::Write,
+{
+    /// Create a new [`SarifReporter`] writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer, count: 0 }
+    }
+
+    /// Destroy the reporter, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> Reporter for SarifReporter<W>
+where
+    W: io::Write,
+{
+    fn begin_run(&mut self) -> Result<()> {
+        self.count = 0;
+        write!(
+            self.writer,
+            r#"{{
+  "$schema": "{SCHEMA}",
+  "version": "{VERSION}",
+  "runs": [
+    {{
+      "tool": {{"driver": {{"name": "{}", "version": "{}", "informationUri": "{}"}}}},
+      "columnKind": "utf16CodeUnits",
+      "results": ["#,
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            env!("CARGO_PKG_REPOSITORY"),
+        )?;
+        Ok(())
+    }
+
+    fn report_match(&mut self, file: &SourceFile<'_>, r#match: &LintMatch) -> Result<()> {
+        let LintMatch {
+            lint_name,
+            message,
+            severity,
+            range,
+            fix: _,
+        } = r#match;
+
+        // NB: The report is written by hand, with all strings escaped
+        //     via `crate::json::escape`; see there for the reasoning.
+        let start = range.start_point;
+        let end = range.end_point;
+        let separator = if self.count == 0 { "" } else { "," };
+        self.count += 1;
+        write!(
+            self.writer,
+            r#"{separator}
+        {{"ruleId": "{}", "level": "{}", "message": {{"text": "{}"}}, "locations": [{{"physicalLocation": {{"artifactLocation": {{"uri": "{}"}}, "region": {{"startLine": {}, "startColumn": {}, "endLine": {}, "endColumn": {}}}}}}}]}}"#,
+            escape(lint_name),
+            sarif_level(*severity),
+            escape(message),
+            escape(&sarif_uri(file.path())),
+            start.row + 1,
+            sarif_column(file, start.row, start.col),
+            end.row + 1,
+            sarif_column(file, end.row, end.col),
+        )?;
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<()> {
+        let indent = if self.count > 0 { "\n      " } else { "" };
+        write!(
+            self.writer,
+            "{indent}]
+    }}
+  ]
+}}
+"
+        )?;
+        let () = self.writer.flush()?;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::formatdoc;
+    use indoc::indoc;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Point;
+    use crate::Range;
+
+
+    /// Check that paths are percent-encoded as necessary.
+    #[test]
+    fn uri_encoding() {
+        assert_eq!(sarif_uri(Path::new("src/a_b-c.bpf.c")), "src/a_b-c.bpf.c");
+        assert_eq!(
+            sarif_uri(Path::new("my dir/100%/ä.bpf.c")),
+            "my%20dir/100%25/%C3%A4.bpf.c"
+        );
+    }
+
+    /// Check that we produce the expected SARIF report.
+    #[test]
+    fn sarif_reporting() {
+        let code = indoc! { r#"
+          int x;
+          /* ä */ foo("a");
+          bar(
+          );
+        "# };
+        let matches = [
+            LintMatch {
+                lint_name: "foo".to_string(),
+                message: "`foo` is \"bad\"".to_string(),
+                severity: Severity::Warning,
+                range: Range {
+                    bytes: 16..19,
+                    start_point: Point { row: 1, col: 9 },
+                    end_point: Point { row: 1, col: 12 },
+                },
+                fix: None,
+            },
+            LintMatch {
+                lint_name: "bar".to_string(),
+                message: "bar".to_string(),
+                severity: Severity::Note,
+                range: Range {
+                    bytes: 26..32,
+                    start_point: Point { row: 2, col: 0 },
+                    end_point: Point { row: 3, col: 1 },
+                },
+                fix: None,
+            },
+        ];
+
+        let mut reporter = SarifReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let file = SourceFile::new(Path::new("a b.bpf.c"), code.as_bytes());
+        let () = reporter.report_file(&file, &matches).unwrap();
+        let () = reporter.end_run().unwrap();
+
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        // The `ä` takes up two bytes, but only a single UTF-16 code
+        // unit.
+        let expected = formatdoc! { r#"
+          {{
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [
+              {{
+                "tool": {{"driver": {{"name": "bpflint", "version": "{version}", "informationUri": "https://github.com/d-e-s-o/bpflint"}}}},
+                "columnKind": "utf16CodeUnits",
+                "results": [
+                  {{"ruleId": "foo", "level": "warning", "message": {{"text": "`foo` is \"bad\""}}, "locations": [{{"physicalLocation": {{"artifactLocation": {{"uri": "a%20b.bpf.c"}}, "region": {{"startLine": 2, "startColumn": 9, "endLine": 2, "endColumn": 12}}}}}}]}},
+                  {{"ruleId": "bar", "level": "note", "message": {{"text": "bar"}}, "locations": [{{"physicalLocation": {{"artifactLocation": {{"uri": "a%20b.bpf.c"}}, "region": {{"startLine": 3, "startColumn": 1, "endLine": 4, "endColumn": 2}}}}}}]}}
+                ]
+              }}
+            ]
+          }}
+        "#, version = env!("CARGO_PKG_VERSION") };
+        assert_eq!(report, expected);
+
+        // An empty report still contains a run.
+        let mut reporter = SarifReporter::new(Vec::new());
+        let () = reporter.begin_run().unwrap();
+        let () = reporter.end_run().unwrap();
+        let report = String::from_utf8(reporter.into_inner()).unwrap();
+        assert!(
+            report.ends_with("\"results\": []\n    }\n  ]\n}\n"),
+            "{report}"
+        );
+    }
+}
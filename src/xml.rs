@@ -0,0 +1,53 @@
+use std::borrow::Cow;
+
+
+/// Escape `text` for inclusion in XML attribute values and character
+/// data.
+///
+/// Control characters not representable in XML 1.0 are replaced with
+/// the Unicode replacement character.
+pub(crate) fn escape(text: &str) -> Cow<'_, str> {
+    fn needs_escape(c: char) -> bool {
+        matches!(c, '&' | '<' | '>' | '"' | '\'') || is_invalid(c)
+    }
+
+    fn is_invalid(c: char) -> bool {
+        c < '\u{20}' && !matches!(c, '\t' | '\n' | '\r')
+    }
+
+    if !text.contains(needs_escape) {
+        return Cow::Borrowed(text)
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if is_invalid(c) => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Check that text is escaped as expected.
+    #[test]
+    fn escaping() {
+        assert!(matches!(escape("foo bar"), Cow::Borrowed("foo bar")));
+        assert_eq!(
+            escape(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;"
+        );
+        assert_eq!(escape("a\tb\nc\u{1}d"), "a\tb\nc\u{fffd}d");
+    }
+}
@@ -1,5 +1,7 @@
 //! Tests for the `probe-read` lint.
 
+use bpflint::lint;
+
 use indoc::indoc;
 
 use pretty_assertions::assert_eq;
@@ -30,3 +32,18 @@ fn basic() {
     "# };
     assert_eq!(lint_report(code), expected);
 }
+
+/// Check that the lint suggests replacing the helper with
+/// `bpf_probe_read_kernel()`.
+#[test]
+fn fix() {
+    let code = indoc! { r#"
+      bpf_probe_read(dst, sizeof(*dst), src);
+    "# };
+
+    let matches = lint(code.as_bytes()).unwrap();
+    assert_eq!(matches.len(), 1);
+    let edit = matches[0].fix_edit().unwrap();
+    assert_eq!(edit.range.bytes, 0..14);
+    assert_eq!(edit.text, "bpf_probe_read_kernel");
+}